@@ -0,0 +1,410 @@
+#![deny(missing_docs)]
+
+//! `ocaml-build` generates OCaml `external` declarations for functions exported using
+//! [ocaml-rs](https://github.com/zshipko/ocaml-rs) and type declarations for types that derive
+//! `ToValue` or `FromValue`, it is meant to be called from `build.rs`:
+//!
+//! ```rust,no_run
+//! fn main() -> std::io::Result<()> {
+//!     ocaml_build::Sigs::new("src/rust.ml").generate()
+//! }
+//! ```
+//!
+//! This will scan all Rust files in `src` and write `src/rust.ml` and `src/rust.mli`.
+//!
+//! The OCaml types are determined by the Rust argument and return types, see the "Type
+//! conversion" section of the `ocaml-rs` README. Types that cannot be converted automatically
+//! (for example `native_func` arguments, which are all `ocaml::Value`) can be specified using
+//! `#[ocaml::sig("...")]`, which can also be used on types to provide the right-hand side of the
+//! type declaration:
+//!
+//! ```rust,ignore
+//! #[ocaml::native_func]
+//! #[ocaml::sig("int -> int")]
+//! pub fn incr(value: ocaml::Value) -> ocaml::Value {
+//!     ocaml::Value::int(value.int_val() + 1)
+//! }
+//! ```
+//!
+//! Native functions whose arguments and return value are all `f64` are marked `[@@unboxed]`.
+//! `[@@noalloc]` is never added automatically, since the function must not allocate, raise an
+//! exception or call back into OCaml, it can be requested using `#[ocaml::sig(noalloc)]` or
+//! `#[ocaml::sig("float -> float", noalloc)]`.
+//!
+//! Derived types are written as a single recursive type declaration, using the same layout as the
+//! derive implementations: constructors are declared in the same order as the Rust variants,
+//! `#[ocaml(unboxed)]` types are marked `[@@unboxed]` and `#[ocaml(floats_array)]` structs
+//! become float records.
+
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+
+mod sig;
+mod types;
+
+pub use sig::{ocaml_type, snake_case, Type};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Func,
+    Native,
+    Bytecode,
+    Extern,
+}
+
+struct Func {
+    name: String,
+    kind: Kind,
+    args: Vec<syn::Type>,
+    ret: Option<syn::Type>,
+    sig: Option<String>,
+    noalloc: bool,
+}
+
+fn attr_name(attr: &syn::Attribute) -> String {
+    attr.path
+        .segments
+        .iter()
+        .map(|x| x.ident.to_string())
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+/// Contents of `#[ocaml::sig("...", noalloc)]`
+#[derive(Default)]
+pub(crate) struct SigAttr {
+    /// OCaml signature
+    pub sig: Option<String>,
+    /// Mark the external `[@@noalloc]`
+    pub noalloc: bool,
+}
+
+/// Returns the contents of `#[ocaml::sig(...)]`, the signature string and `noalloc` are both
+/// optional
+pub(crate) fn sig_attr(attrs: &[syn::Attribute]) -> Result<SigAttr, Error> {
+    let mut dest = SigAttr::default();
+    for attr in attrs {
+        let name = attr_name(attr);
+        if name != "ocaml::sig" && name != "ocaml_sig" && name != "sig" {
+            continue;
+        }
+
+        let args = attr
+            .parse_args_with(
+                syn::punctuated::Punctuated::<syn::NestedMeta, syn::Token![,]>::parse_terminated,
+            )
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        for arg in args {
+            match arg {
+                syn::NestedMeta::Lit(syn::Lit::Str(s)) if dest.sig.is_none() => {
+                    dest.sig = Some(s.value())
+                }
+                syn::NestedMeta::Meta(syn::Meta::Path(p)) if p.is_ident("noalloc") => {
+                    dest.noalloc = true
+                }
+                _ => {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        "ocaml::sig expects a string argument and/or noalloc",
+                    ))
+                }
+            }
+        }
+    }
+    Ok(dest)
+}
+
+fn func_kind(item: &syn::ItemFn) -> Option<Kind> {
+    let mut no_mangle = false;
+    for attr in &item.attrs {
+        match attr_name(attr).as_str() {
+            "ocaml::func" | "ocaml_func" | "func" => return Some(Kind::Func),
+            "ocaml::native_func" | "ocaml_native_func" | "native_func" => {
+                return Some(Kind::Native)
+            }
+            "ocaml::bytecode_func" | "ocaml_bytecode_func" | "bytecode_func" => {
+                return Some(Kind::Bytecode)
+            }
+            "no_mangle" => no_mangle = true,
+            _ => (),
+        }
+    }
+
+    if no_mangle && item.sig.abi.is_some() {
+        return Some(Kind::Extern);
+    }
+
+    None
+}
+
+fn collect_funcs(items: &[syn::Item], dest: &mut Vec<Func>) -> Result<(), Error> {
+    for item in items {
+        match item {
+            syn::Item::Fn(f) => {
+                let kind = match func_kind(f) {
+                    Some(k) => k,
+                    None => continue,
+                };
+
+                let args = f
+                    .sig
+                    .inputs
+                    .iter()
+                    .filter_map(|x| match x {
+                        syn::FnArg::Typed(t) if !sig::is_runtime(&t.ty) => {
+                            Some(t.ty.as_ref().clone())
+                        }
+                        _ => None,
+                    })
+                    .collect();
+
+                let ret = match &f.sig.output {
+                    syn::ReturnType::Default => None,
+                    syn::ReturnType::Type(_, t) => Some(t.as_ref().clone()),
+                };
+
+                let attr = sig_attr(&f.attrs)?;
+                dest.push(Func {
+                    name: f.sig.ident.to_string(),
+                    kind,
+                    args,
+                    ret,
+                    sig: attr.sig,
+                    noalloc: attr.noalloc,
+                })
+            }
+            syn::Item::Mod(m) => {
+                if let Some((_, items)) = &m.content {
+                    collect_funcs(items, dest)?
+                }
+            }
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+fn signature(f: &Func, pointers: &mut Vec<String>) -> Result<String, Error> {
+    if let Some(s) = &f.sig {
+        return Ok(s.clone());
+    }
+
+    let mut vars = sig::Vars::default();
+    let mut parts = Vec::new();
+    for arg in &f.args {
+        sig::pointer_types(arg, pointers);
+        parts.push(ocaml_type(arg, &mut vars)?.fmt_external());
+    }
+
+    if parts.is_empty() {
+        parts.push(String::from("unit"));
+    }
+
+    match &f.ret {
+        Some(t) => {
+            sig::pointer_types(t, pointers);
+            parts.push(ocaml_type(t, &mut vars)?.fmt_external())
+        }
+        None => parts.push(String::from("unit")),
+    }
+
+    Ok(parts.join(" -> "))
+}
+
+fn is_unboxed(f: &Func) -> bool {
+    !f.args.is_empty() && f.args.iter().all(sig::is_f64) && f.ret.as_ref().is_some_and(sig::is_f64)
+}
+
+/// `[@@noalloc]` is only added when requested using `#[ocaml::sig(noalloc)]`, since the function
+/// must not allocate, raise or call back into OCaml
+fn noalloc(f: &Func) -> &'static str {
+    if f.noalloc {
+        " [@@noalloc]"
+    } else {
+        ""
+    }
+}
+
+fn externals(funcs: &[Func], pointers: &mut Vec<String>) -> Result<Vec<String>, Error> {
+    let find = |name: &str, kinds: &[Kind]| {
+        funcs
+            .iter()
+            .find(|x| x.name == name && kinds.contains(&x.kind))
+    };
+
+    let mut dest = Vec::new();
+    for f in funcs {
+        let name = &f.name;
+        let line = match f.kind {
+            Kind::Func if f.args.len() > 5 => format!(
+                "external {}: {} = \"{}_bytecode\" \"{}\"{}",
+                name,
+                signature(f, pointers)?,
+                name,
+                name,
+                noalloc(f)
+            ),
+            Kind::Func => format!(
+                "external {}: {} = \"{}\"{}",
+                name,
+                signature(f, pointers)?,
+                name,
+                noalloc(f)
+            ),
+            Kind::Native | Kind::Extern => {
+                let bytecode = format!("{}_bytecode", name);
+                if find(&bytecode, &[Kind::Bytecode]).is_some() {
+                    continue;
+                }
+
+                if f.kind == Kind::Extern && f.sig.is_none() {
+                    continue;
+                }
+
+                format!(
+                    "external {}: {} = \"{}\"{}",
+                    name,
+                    signature(f, pointers)?,
+                    name,
+                    noalloc(f)
+                )
+            }
+            Kind::Bytecode => {
+                let native = name
+                    .strip_suffix("_bytecode")
+                    .and_then(|x| find(x, &[Kind::Native, Kind::Extern]));
+                match native {
+                    Some(native) => {
+                        let sig = match &native.sig {
+                            Some(s) => s.clone(),
+                            None => signature(f, pointers)?,
+                        };
+                        let unboxed = if is_unboxed(native) {
+                            " [@@unboxed]"
+                        } else {
+                            ""
+                        };
+                        format!(
+                            "external {}: {} = \"{}\" \"{}\"{}{}",
+                            native.name,
+                            sig,
+                            name,
+                            native.name,
+                            unboxed,
+                            noalloc(native)
+                        )
+                    }
+                    None => format!(
+                        "external {}: {} = \"{}\"",
+                        name,
+                        signature(f, pointers)?,
+                        name
+                    ),
+                }
+            }
+        };
+        dest.push(line);
+    }
+
+    Ok(dest)
+}
+
+/// Generates OCaml source from a list of parsed Rust files
+pub fn generate_ml(files: &[syn::File]) -> Result<String, Error> {
+    let mut funcs = Vec::new();
+    let mut derived = Vec::new();
+    let mut pointers = Vec::new();
+    for file in files {
+        collect_funcs(&file.items, &mut funcs)?;
+        types::collect_types(&file.items, &mut derived);
+        types::collect_custom(&file.items, &mut pointers);
+    }
+
+    let externals = externals(&funcs, &mut pointers)?;
+    pointers.retain(|x| !derived.iter().any(|t| &t.name() == x));
+
+    let mut dest = String::from("(* Generated by ocaml-build, do not edit *)\n\n");
+
+    if !pointers.is_empty() {
+        for p in pointers {
+            dest.push_str(&format!("type {}\n", p));
+        }
+        dest.push('\n');
+    }
+
+    if !derived.is_empty() {
+        dest.push_str(&types::type_decls(&derived)?);
+        dest.push('\n');
+    }
+
+    for e in externals {
+        dest.push_str(&e);
+        dest.push('\n');
+    }
+
+    Ok(dest)
+}
+
+fn rust_files(dir: &Path, dest: &mut Vec<PathBuf>) -> Result<(), Error> {
+    let mut entries = std::fs::read_dir(dir)?
+        .map(|x| x.map(|x| x.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            rust_files(&path, dest)?
+        } else if path.extension().is_some_and(|x| x == "rs") {
+            dest.push(path)
+        }
+    }
+
+    Ok(())
+}
+
+/// `Sigs` is used to generate OCaml `external` declarations from a Rust source directory
+pub struct Sigs {
+    base_dir: PathBuf,
+    output: PathBuf,
+}
+
+impl Sigs {
+    /// Create a new `Sigs` that will write to `output`, a matching `.mli` file is also
+    /// generated. By default Rust files are read from `src`
+    pub fn new(output: impl AsRef<Path>) -> Sigs {
+        Sigs {
+            base_dir: PathBuf::from("src"),
+            output: output.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Set the directory used to find Rust source files
+    pub fn with_source_dir(mut self, dir: impl AsRef<Path>) -> Sigs {
+        self.base_dir = dir.as_ref().to_path_buf();
+        self
+    }
+
+    /// Parse Rust files and write the `.ml` and `.mli` files
+    pub fn generate(&self) -> Result<(), Error> {
+        let mut paths = Vec::new();
+        rust_files(&self.base_dir, &mut paths)?;
+
+        let mut files = Vec::new();
+        for path in paths {
+            println!("cargo:rerun-if-changed={}", path.display());
+            let src = std::fs::read_to_string(&path)?;
+            let file = syn::parse_file(&src).map_err(|e| {
+                Error::new(ErrorKind::InvalidData, format!("{}: {}", path.display(), e))
+            })?;
+            files.push(file);
+        }
+
+        let ml = generate_ml(&files)?;
+        std::fs::write(&self.output, &ml)?;
+        std::fs::write(self.output.with_extension("mli"), &ml)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests;
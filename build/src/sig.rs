@@ -0,0 +1,380 @@
+use std::io::{Error, ErrorKind};
+
+/// OCaml type expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    /// Named type, `int`, `Bigarray.c_layout`, ...
+    Name(String),
+
+    /// Type variable, `'a`
+    Var(String),
+
+    /// Type constructor applied to arguments, `int list`, `(int, string) result`, ...
+    App(Vec<Type>, String),
+
+    /// Tuple, `int * float`
+    Tuple(Vec<Type>),
+
+    /// Function type, `int -> string`
+    Arrow(Vec<Type>),
+}
+
+impl Type {
+    fn name(s: &str) -> Type {
+        Type::Name(s.to_string())
+    }
+
+    fn app(arg: Type, s: &str) -> Type {
+        Type::App(vec![arg], s.to_string())
+    }
+
+    pub(crate) fn fmt_arg(&self) -> String {
+        match self {
+            Type::Tuple(_) | Type::Arrow(_) => format!("({})", self),
+            _ => self.to_string(),
+        }
+    }
+
+    /// Formats an argument or return type of an `external` declaration, function types are
+    /// wrapped in parentheses so they don't change the arity of the external
+    pub(crate) fn fmt_external(&self) -> String {
+        match self {
+            Type::Arrow(_) => format!("({})", self),
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Type::Name(s) | Type::Var(s) => write!(f, "{}", s),
+            Type::App(args, name) if args.len() == 1 => write!(f, "{} {}", args[0].fmt_arg(), name),
+            Type::App(args, name) => {
+                let args: Vec<_> = args.iter().map(|x| x.to_string()).collect();
+                write!(f, "({}) {}", args.join(", "), name)
+            }
+            Type::Tuple(items) => {
+                let items: Vec<_> = items.iter().map(|x| x.fmt_arg()).collect();
+                write!(f, "{}", items.join(" * "))
+            }
+            Type::Arrow(items) => {
+                let items: Vec<_> = items.iter().map(|x| x.fmt_external()).collect();
+                write!(f, "{}", items.join(" -> "))
+            }
+        }
+    }
+}
+
+/// Converts Rust type names to OCaml type names: `MyType` becomes `my_type`
+pub fn snake_case(s: &str) -> String {
+    let mut dest = String::new();
+    let mut prev_lower = false;
+    for c in s.chars() {
+        if c.is_uppercase() && prev_lower {
+            dest.push('_');
+        }
+        prev_lower = c.is_lowercase() || c.is_numeric();
+        dest.extend(c.to_lowercase());
+    }
+    dest
+}
+
+/// Tracks type variables used in a single signature, each `Value` gets a new variable
+#[derive(Default)]
+pub struct Vars {
+    count: usize,
+    generics: Vec<String>,
+    no_fresh: bool,
+}
+
+impl Vars {
+    /// Type variables for a type declaration, generic parameters are mapped to type variables
+    /// and `Value` is not allowed
+    pub fn with_generics(generics: Vec<String>) -> Vars {
+        Vars {
+            count: 0,
+            generics,
+            no_fresh: true,
+        }
+    }
+
+    /// Returns a fresh type variable
+    pub fn fresh(&mut self) -> Option<Type> {
+        if self.no_fresh {
+            return None;
+        }
+
+        let n = self.count;
+        self.count += 1;
+        let c = (b'a' + (n % 26) as u8) as char;
+        if n < 26 {
+            Some(Type::Var(format!("'{}", c)))
+        } else {
+            Some(Type::Var(format!("'{}{}", c, n / 26)))
+        }
+    }
+
+    fn generic(&self, name: &str) -> Option<Type> {
+        if self.generics.iter().any(|x| x == name) {
+            Some(Type::Var(type_var(name)))
+        } else {
+            None
+        }
+    }
+}
+
+/// Converts a Rust generic parameter to an OCaml type variable: `T` becomes `'t`
+pub fn type_var(name: &str) -> String {
+    format!("'{}", snake_case(name))
+}
+
+fn error(ty: &syn::Type) -> Error {
+    Error::new(
+        ErrorKind::InvalidData,
+        format!(
+            "unable to convert Rust type to OCaml type: {}, use #[ocaml::sig] to specify the OCaml type",
+            quote_type(ty)
+        ),
+    )
+}
+
+fn quote_type(ty: &syn::Type) -> String {
+    match ty {
+        syn::Type::Path(p) => p
+            .path
+            .segments
+            .iter()
+            .map(|x| x.ident.to_string())
+            .collect::<Vec<_>>()
+            .join("::"),
+        _ => String::from("<type>"),
+    }
+}
+
+fn generic_args(segment: &syn::PathSegment) -> Vec<&syn::Type> {
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => args
+            .args
+            .iter()
+            .filter_map(|x| match x {
+                syn::GenericArgument::Type(t) => Some(t),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn bigarray_kind(ty: &syn::Type) -> Result<(Type, Type), Error> {
+    let segment = match ty {
+        syn::Type::Path(p) => p.path.segments.last(),
+        _ => None,
+    };
+    let mut name = segment.map(|x| x.ident.to_string());
+    if let Some(s) = segment.filter(|x| x.ident == "Complex") {
+        // `Complex<f32>` and `Complex<f64>` use the name of the argument to pick the element type
+        match generic_args(s).first() {
+            Some(syn::Type::Path(p)) if p.path.is_ident("f32") => name = Some("Complex32".into()),
+            Some(syn::Type::Path(p)) if p.path.is_ident("f64") => name = Some("Complex64".into()),
+            _ => return Err(error(ty)),
+        }
+    }
+    let (kind, elt) = match name.as_deref() {
+        Some("f32") => ("float", "float32_elt"),
+        Some("f64") => ("float", "float64_elt"),
+        Some("i8") => ("int", "int8_signed_elt"),
+        Some("u8") => ("int", "int8_unsigned_elt"),
+        Some("i16") => ("int", "int16_signed_elt"),
+        Some("u16") => ("int", "int16_unsigned_elt"),
+        Some("i32") => ("int32", "int32_elt"),
+        Some("i64") => ("int64", "int64_elt"),
+        Some("isize") => ("nativeint", "nativeint_elt"),
+        Some("CamlInt") => ("int", "int_elt"),
+        Some("Char") => ("char", "int8_unsigned_elt"),
+        Some("Complex32") => ("Complex.t", "complex32_elt"),
+        Some("Complex64") => ("Complex.t", "complex64_elt"),
+        _ => return Err(error(ty)),
+    };
+    Ok((Type::name(kind), Type::Name(format!("Bigarray.{}", elt))))
+}
+
+fn bigarray_layout(ty: Option<&syn::Type>) -> Result<Type, Error> {
+    let ty = match ty {
+        Some(ty) => ty,
+        None => return Ok(Type::name("Bigarray.c_layout")),
+    };
+    let name = match ty {
+        syn::Type::Path(p) => p.path.segments.last().map(|x| x.ident.to_string()),
+        _ => None,
+    };
+    match name.as_deref() {
+        Some("CLayout") => Ok(Type::name("Bigarray.c_layout")),
+        Some("FortranLayout") => Ok(Type::name("Bigarray.fortran_layout")),
+        _ => Err(error(ty)),
+    }
+}
+
+fn is_value(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(p) => p.path.segments.last().is_some_and(|x| x.ident == "Value"),
+        _ => false,
+    }
+}
+
+fn is_u8(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(p) => p.path.is_ident("u8"),
+        _ => false,
+    }
+}
+
+/// Convert a Rust type to the matching OCaml type
+pub fn ocaml_type(ty: &syn::Type, vars: &mut Vars) -> Result<Type, Error> {
+    match ty {
+        syn::Type::Reference(r) => match r.elem.as_ref() {
+            syn::Type::Slice(s) if is_u8(&s.elem) => Ok(Type::name("bytes")),
+            syn::Type::Slice(s) if is_value(&s.elem) => match vars.fresh() {
+                Some(v) => Ok(Type::app(v, "array")),
+                None => Err(error(ty)),
+            },
+            syn::Type::Slice(s) => Ok(Type::app(ocaml_type(&s.elem, vars)?, "array")),
+            t => ocaml_type(t, vars),
+        },
+        syn::Type::Paren(p) => ocaml_type(&p.elem, vars),
+        syn::Type::Group(g) => ocaml_type(&g.elem, vars),
+        syn::Type::Tuple(t) if t.elems.is_empty() => Ok(Type::name("unit")),
+        syn::Type::Tuple(t) => Ok(Type::Tuple(
+            t.elems
+                .iter()
+                .map(|x| ocaml_type(x, vars))
+                .collect::<Result<_, _>>()?,
+        )),
+        syn::Type::Path(p) => {
+            let segment = match p.path.segments.last() {
+                Some(s) => s,
+                None => return Err(error(ty)),
+            };
+            let args = generic_args(segment);
+            let name = segment.ident.to_string();
+            let arg = |i: usize, vars: &mut Vars| match args.get(i) {
+                Some(t) => ocaml_type(t, vars),
+                None => Err(error(ty)),
+            };
+            if p.path.segments.len() == 1 && args.is_empty() {
+                if let Some(v) = vars.generic(&name) {
+                    return Ok(v);
+                }
+            }
+            let t = match name.as_str() {
+                "isize" | "usize" | "i8" | "u8" | "i16" | "u16" | "Int" | "Uint" => {
+                    Type::name("int")
+                }
+                "i32" | "u32" => Type::name("int32"),
+                "i64" | "u64" => Type::name("int64"),
+                "f32" | "f64" | "Float" => Type::name("float"),
+                "bool" => Type::name("bool"),
+                "char" => Type::name("char"),
+                "str" | "String" => Type::name("string"),
+                "Value" => match vars.fresh() {
+                    Some(v) => v,
+                    None => return Err(error(ty)),
+                },
+                "Option" => Type::app(arg(0, vars)?, "option"),
+                "Result" => arg(0, vars)?,
+                "Box" => arg(0, vars)?,
+                "Vec" | "Array" => Type::app(arg(0, vars)?, "array"),
+                "List" | "LinkedList" => Type::app(arg(0, vars)?, "list"),
+                "BTreeMap" => Type::app(Type::Tuple(vec![arg(0, vars)?, arg(1, vars)?]), "list"),
+                "Fn1" | "Fn2" | "Fn3" | "Fn4" | "Fn5" => {
+                    let arity: usize = name[2..].parse().unwrap_or_default();
+                    if args.len() != arity + 1 {
+                        return Err(error(ty));
+                    }
+                    Type::Arrow(
+                        args.iter()
+                            .map(|x| ocaml_type(x, vars))
+                            .collect::<Result<_, _>>()?,
+                    )
+                }
+                "Pointer" => match args.first() {
+                    Some(syn::Type::Path(p)) => match p.path.segments.last() {
+                        Some(s) => Type::Name(snake_case(&s.ident.to_string())),
+                        None => return Err(error(ty)),
+                    },
+                    _ => return Err(error(ty)),
+                },
+                "Array1" | "Array2" | "Array3" | "Genarray" => {
+                    let (kind, elt) = match args.first() {
+                        Some(t) => bigarray_kind(t)?,
+                        None => return Err(error(ty)),
+                    };
+                    let layout = bigarray_layout(args.get(1).copied())?;
+                    Type::App(vec![kind, elt, layout], format!("Bigarray.{}.t", name))
+                }
+                _ => {
+                    let args = args
+                        .iter()
+                        .map(|x| ocaml_type(x, vars))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    let name = snake_case(&name);
+                    if args.is_empty() {
+                        Type::Name(name)
+                    } else {
+                        Type::App(args, name)
+                    }
+                }
+            };
+            Ok(t)
+        }
+        _ => Err(error(ty)),
+    }
+}
+
+/// Returns true when the type is `f64`, used to detect unboxed functions
+pub fn is_f64(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(p) => p.path.is_ident("f64"),
+        _ => false,
+    }
+}
+
+/// Returns true for `&mut ocaml::Runtime` arguments, which aren't passed from OCaml
+pub fn is_runtime(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Reference(r) if r.mutability.is_some() => match r.elem.as_ref() {
+            syn::Type::Path(p) => p.path.segments.last().is_some_and(|x| x.ident == "Runtime"),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Returns a list of `Pointer<T>` types used in `ty`
+pub fn pointer_types(ty: &syn::Type, dest: &mut Vec<String>) {
+    match ty {
+        syn::Type::Reference(r) => pointer_types(&r.elem, dest),
+        syn::Type::Slice(s) => pointer_types(&s.elem, dest),
+        syn::Type::Paren(p) => pointer_types(&p.elem, dest),
+        syn::Type::Group(g) => pointer_types(&g.elem, dest),
+        syn::Type::Tuple(t) => t.elems.iter().for_each(|x| pointer_types(x, dest)),
+        syn::Type::Path(p) => {
+            if let Some(segment) = p.path.segments.last() {
+                let args = generic_args(segment);
+                if segment.ident == "Pointer" {
+                    if let Some(syn::Type::Path(p)) = args.first() {
+                        if let Some(s) = p.path.segments.last() {
+                            let name = snake_case(&s.ident.to_string());
+                            if !dest.contains(&name) {
+                                dest.push(name)
+                            }
+                        }
+                    }
+                } else {
+                    args.iter().for_each(|x| pointer_types(x, dest))
+                }
+            }
+        }
+        _ => (),
+    }
+}
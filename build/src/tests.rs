@@ -0,0 +1,312 @@
+use crate::generate_ml;
+
+fn generate(src: &str) -> String {
+    let file = syn::parse_file(src).unwrap();
+    generate_ml(&[file]).unwrap()
+}
+
+#[test]
+fn test_func() {
+    let ml = generate(
+        r#"
+        #[ocaml::func]
+        pub fn incr_example(mut e: Example) -> Example {
+            e
+        }
+
+        #[ocaml::func]
+        pub fn build_tuple(i: ocaml::Int) -> (ocaml::Int, ocaml::Int, ocaml::Int) {
+            (i + 1, i + 2, i + 3)
+        }
+
+        #[ocaml::func]
+        pub fn average(arr: ocaml::Array<f64>) -> Result<f64, ocaml::Error> {
+            Ok(0.0)
+        }
+
+        #[ocaml::func]
+        pub fn pairs(x: Vec<(String, Option<i64>)>, y: &[u8]) -> ocaml::List<ocaml::Value> {
+            ocaml::List::empty()
+        }
+
+        #[ocaml::func]
+        pub fn unit() {}
+
+        #[ocaml::func]
+        pub fn hello(rt: &mut ocaml::Runtime, name: String) -> ocaml::Value {
+            rt.alloc(name).value()
+        }
+    "#,
+    );
+
+    assert!(ml.contains("external incr_example: example -> example = \"incr_example\"\n"));
+    assert!(ml.contains("external build_tuple: int -> int * int * int = \"build_tuple\"\n"));
+    assert!(ml.contains("external average: float array -> float = \"average\"\n"));
+    assert!(ml.contains(
+        "external pairs: (string * int64 option) array -> bytes -> 'a list = \"pairs\"\n"
+    ));
+    assert!(ml.contains("external unit: unit -> unit = \"unit\"\n"));
+    assert!(ml.contains("external hello: string -> 'a = \"hello\"\n"));
+}
+
+#[test]
+fn test_closures() {
+    let ml = generate(
+        r#"
+        #[ocaml::func]
+        pub fn apply2(f: ocaml::Fn2<ocaml::Int, f64, String>, a: ocaml::Int) -> ocaml::Fn1<f64, String> {
+            f
+        }
+
+        #[ocaml::func]
+        pub fn map(f: Fn1<ocaml::Value, ocaml::Value>, l: ocaml::List<ocaml::Value>) -> Vec<Fn1<(), ()>> {
+            Vec::new()
+        }
+    "#,
+    );
+
+    assert!(ml.contains(
+        "external apply2: (int -> float -> string) -> int -> (float -> string) = \"apply2\"\n"
+    ));
+    assert!(ml.contains("external map: ('a -> 'b) -> 'c list -> (unit -> unit) array = \"map\"\n"));
+}
+
+#[test]
+fn test_bytecode() {
+    let ml = generate(
+        r#"
+        #[ocaml::func]
+        pub fn many(a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> f64 {
+            a
+        }
+
+        #[no_mangle]
+        pub extern "C" fn incrf(input: f64) -> f64 {
+            input + 1.0
+        }
+
+        #[ocaml::bytecode_func]
+        pub fn incrf_bytecode(input: f64) -> f64 {
+            incrf(input)
+        }
+
+        #[no_mangle]
+        #[ocaml::sig(noalloc)]
+        pub extern "C" fn decrf(input: f64) -> f64 {
+            input - 1.0
+        }
+
+        #[ocaml::bytecode_func]
+        pub fn decrf_bytecode(input: f64) -> f64 {
+            decrf(input)
+        }
+
+        #[ocaml::native_func]
+        #[ocaml::sig("int -> int")]
+        pub fn incr(value: ocaml::Value) -> ocaml::Value {
+            value
+        }
+
+        #[ocaml::func]
+        #[ocaml::sig("int -> int", noalloc)]
+        pub fn ident_int(value: ocaml::Value) -> ocaml::Value {
+            value
+        }
+
+        #[ocaml::native_func]
+        pub fn ident(value: ocaml::Value) -> ocaml::Value {
+            value
+        }
+    "#,
+    );
+
+    assert!(ml.contains(
+        "external many: float -> float -> float -> float -> float -> float -> float = \"many_bytecode\" \"many\"\n"
+    ));
+    assert!(
+        ml.contains("external incrf: float -> float = \"incrf_bytecode\" \"incrf\" [@@unboxed]\n")
+    );
+    assert!(ml.contains(
+        "external decrf: float -> float = \"decrf_bytecode\" \"decrf\" [@@unboxed] [@@noalloc]\n"
+    ));
+    assert!(ml.contains("external incr: int -> int = \"incr\"\n"));
+    assert!(ml.contains("external ident_int: int -> int = \"ident_int\" [@@noalloc]\n"));
+    assert!(ml.contains("external ident: 'a -> 'b = \"ident\"\n"));
+}
+
+#[test]
+fn test_pointer() {
+    let ml = generate(
+        r#"
+        #[ocaml::func]
+        pub fn my_type_new() -> ocaml::Pointer<MyType> {
+            ocaml::Pointer::alloc_custom(MyType)
+        }
+
+        #[ocaml::func]
+        pub fn my_type_data(t: ocaml::Pointer<MyType>) -> ocaml::bigarray::Array1<u8> {
+            t.as_ref().data()
+        }
+    "#,
+    );
+
+    assert!(ml.contains("type my_type\n"));
+    assert!(ml.contains("external my_type_new: unit -> my_type = \"my_type_new\"\n"));
+    assert!(ml.contains(
+        "external my_type_data: my_type -> (int, Bigarray.int8_unsigned_elt, Bigarray.c_layout) Bigarray.Array1.t = \"my_type_data\"\n"
+    ));
+}
+
+#[test]
+fn test_bigarray_kinds() {
+    let ml = generate(
+        r#"
+        #[ocaml::func]
+        pub fn complex(a: Array1<ocaml::bigarray::Complex<f64>>) -> Array2<Complex<f32>> {
+            todo!()
+        }
+
+        #[ocaml::func]
+        pub fn gen(a: ocaml::bigarray::Genarray<f32>) {}
+
+        #[ocaml::func]
+        pub fn ints(a: Array1<ocaml::bigarray::CamlInt>, b: Array1<isize>) -> Array1<Char> {
+            todo!()
+        }
+    "#,
+    );
+
+    assert!(ml.contains(
+        "external complex: (Complex.t, Bigarray.complex64_elt, Bigarray.c_layout) Bigarray.Array1.t -> (Complex.t, Bigarray.complex32_elt, Bigarray.c_layout) Bigarray.Array2.t = \"complex\"\n"
+    ));
+    assert!(ml.contains(
+        "external gen: (float, Bigarray.float32_elt, Bigarray.c_layout) Bigarray.Genarray.t -> unit = \"gen\"\n"
+    ));
+    assert!(ml.contains(
+        "external ints: (int, Bigarray.int_elt, Bigarray.c_layout) Bigarray.Array1.t -> (nativeint, Bigarray.nativeint_elt, Bigarray.c_layout) Bigarray.Array1.t -> (char, Bigarray.int8_unsigned_elt, Bigarray.c_layout) Bigarray.Array1.t = \"ints\"\n"
+    ));
+}
+
+#[test]
+fn test_bigarray_layout() {
+    let ml = generate(
+        r#"
+        #[ocaml::func]
+        pub fn transpose(a: Array2<f64, ocaml::bigarray::FortranLayout>) -> Array2<f64, CLayout> {
+            todo!()
+        }
+    "#,
+    );
+
+    assert!(ml.contains(
+        "external transpose: (float, Bigarray.float64_elt, Bigarray.fortran_layout) Bigarray.Array2.t -> (float, Bigarray.float64_elt, Bigarray.c_layout) Bigarray.Array2.t = \"transpose\"\n"
+    ));
+}
+
+#[test]
+fn test_custom() {
+    let ml = generate(
+        r#"
+        #[derive(ocaml::Custom)]
+        #[ocaml(compare, hash)]
+        pub struct Version(ocaml::Int, ocaml::Int);
+
+        #[ocaml::func]
+        pub fn version_new(a: ocaml::Int, b: ocaml::Int) -> Version {
+            Version(a, b)
+        }
+
+        #[ocaml::func]
+        pub fn version_major(v: ocaml::Pointer<Version>) -> ocaml::Int {
+            v.as_ref().0
+        }
+    "#,
+    );
+
+    assert_eq!(ml.matches("type version\n").count(), 1);
+    assert!(ml.contains("external version_new: int -> int -> version = \"version_new\"\n"));
+    assert!(ml.contains("external version_major: version -> int = \"version_major\"\n"));
+}
+
+#[test]
+fn test_types() {
+    let ml = generate(
+        r#"
+        #[derive(ToValue, FromValue)]
+        enum Enum1 {
+            Empty,
+            First(ocaml::Int),
+            Second(ocaml::Array<&'static str>),
+            Third { a: Struct1, b: (f64, f64) },
+        }
+
+        #[derive(ToValue, FromValue, Default)]
+        struct Struct1 {
+            a: ocaml::Int,
+            b: ocaml::Float,
+            c: Option<String>,
+            d: Option<Enum1>,
+        }
+
+        #[derive(ocaml::ToValue, ocaml::FromValue)]
+        #[ocaml(unboxed)]
+        struct Unboxed(f64);
+
+        #[derive(ocaml::FromValue)]
+        #[ocaml(floats_array)]
+        struct Point {
+            x: f64,
+            y: f64,
+        }
+
+        #[derive(ocaml::ToValue)]
+        struct Pair<'a, A, B>(&'a A, Vec<B>);
+
+        #[ocaml::func]
+        pub fn struct1_get_c(s: Struct1) -> Option<String> {
+            s.c
+        }
+    "#,
+    );
+
+    let expected = r#"type enum1 =
+  | Empty
+  | First of int
+  | Second of string array
+  | Third of {
+      a: struct1;
+      b: float * float;
+    }
+and struct1 = {
+  a: int;
+  b: float;
+  c: string option;
+  d: enum1 option;
+}
+and unboxed = Unboxed of float [@@unboxed]
+and point = {
+  x: float;
+  y: float;
+}
+and ('a, 'b) pair = Pair of 'a * 'b array
+
+external struct1_get_c: struct1 -> string option = "struct1_get_c"
+"#;
+    assert!(ml.ends_with(expected), "{}", ml);
+}
+
+#[test]
+fn test_types_invalid() {
+    let file = syn::parse_file(
+        r#"
+        #[derive(ToValue, FromValue)]
+        #[ocaml(floats_array)]
+        struct Point {
+            x: f64,
+            y: ocaml::Int,
+        }
+    "#,
+    )
+    .unwrap();
+    assert!(generate_ml(&[file]).is_err());
+}
@@ -0,0 +1,252 @@
+use std::io::{Error, ErrorKind};
+
+use crate::sig::{ocaml_type, snake_case, type_var, Type, Vars};
+
+#[derive(Default)]
+struct Attrs {
+    unboxed: bool,
+    floats: bool,
+}
+
+fn error(name: &syn::Ident, msg: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, format!("{}: {}", name, msg))
+}
+
+fn derives(attrs: &[syn::Attribute], names: &[&str]) -> bool {
+    attrs.iter().any(|attr| {
+        if !attr.path.is_ident("derive") {
+            return false;
+        }
+
+        match attr.parse_meta() {
+            Ok(syn::Meta::List(list)) => list.nested.iter().any(|x| match x {
+                syn::NestedMeta::Meta(syn::Meta::Path(p)) => p
+                    .segments
+                    .last()
+                    .is_some_and(|x| names.iter().any(|name| x.ident == name)),
+                _ => false,
+            }),
+            _ => false,
+        }
+    })
+}
+
+fn is_derived(attrs: &[syn::Attribute]) -> bool {
+    derives(attrs, &["ToValue", "FromValue"])
+}
+
+// This should stay in sync with `variant_attrs` in `ocaml-derive`
+fn ocaml_attrs(attrs: &[syn::Attribute]) -> Attrs {
+    let mut acc = Attrs::default();
+    for attr in attrs {
+        if !attr.path.is_ident("ocaml") {
+            continue;
+        }
+
+        if let Ok(syn::Meta::List(list)) = attr.parse_meta() {
+            for meta in list.nested {
+                if let syn::NestedMeta::Meta(syn::Meta::Path(p)) = meta {
+                    if p.is_ident("unboxed") {
+                        acc.unboxed = true;
+                    } else if p.is_ident("floats_array") {
+                        acc.floats = true;
+                    }
+                }
+            }
+        }
+    }
+    acc
+}
+
+/// A Rust type that derives `ToValue` or `FromValue`
+pub struct Derived {
+    ident: syn::Ident,
+    generics: syn::Generics,
+    data: syn::Fields,
+    variants: Option<Vec<syn::Variant>>,
+    attrs: Vec<syn::Attribute>,
+}
+
+impl Derived {
+    /// OCaml type name
+    pub fn name(&self) -> String {
+        snake_case(&self.ident.to_string())
+    }
+}
+
+/// Find all types that derive `ToValue` or `FromValue`
+pub fn collect_types(items: &[syn::Item], dest: &mut Vec<Derived>) {
+    for item in items {
+        match item {
+            syn::Item::Struct(s) if is_derived(&s.attrs) => dest.push(Derived {
+                ident: s.ident.clone(),
+                generics: s.generics.clone(),
+                data: s.fields.clone(),
+                variants: None,
+                attrs: s.attrs.clone(),
+            }),
+            syn::Item::Enum(e) if is_derived(&e.attrs) => dest.push(Derived {
+                ident: e.ident.clone(),
+                generics: e.generics.clone(),
+                data: syn::Fields::Unit,
+                variants: Some(e.variants.iter().cloned().collect()),
+                attrs: e.attrs.clone(),
+            }),
+            syn::Item::Mod(m) => {
+                if let Some((_, items)) = &m.content {
+                    collect_types(items, dest)
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Find all types that derive `Custom`, these are declared as abstract types
+pub fn collect_custom(items: &[syn::Item], dest: &mut Vec<String>) {
+    for item in items {
+        match item {
+            syn::Item::Struct(s) if derives(&s.attrs, &["Custom"]) => {
+                dest.push(snake_case(&s.ident.to_string()))
+            }
+            syn::Item::Enum(e) if derives(&e.attrs, &["Custom"]) => {
+                dest.push(snake_case(&e.ident.to_string()))
+            }
+            syn::Item::Mod(m) => {
+                if let Some((_, items)) = &m.content {
+                    collect_custom(items, dest)
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+fn record(fields: &syn::FieldsNamed, vars: &mut Vars, indent: &str) -> Result<String, Error> {
+    let mut dest = String::from("{\n");
+    for field in &fields.named {
+        let name = match &field.ident {
+            Some(x) => x.to_string().trim_start_matches("r#").to_string(),
+            None => continue,
+        };
+        let t = ocaml_type(&field.ty, vars)?;
+        dest.push_str(&format!("{}  {}: {};\n", indent, name, t));
+    }
+    dest.push_str(indent);
+    dest.push('}');
+    Ok(dest)
+}
+
+fn constructor(
+    name: &syn::Ident,
+    fields: &syn::Fields,
+    vars: &mut Vars,
+    indent: &str,
+) -> Result<String, Error> {
+    match fields {
+        syn::Fields::Unit => Ok(name.to_string()),
+        syn::Fields::Unnamed(f) if f.unnamed.is_empty() => Ok(name.to_string()),
+        syn::Fields::Named(f) if f.named.is_empty() => Ok(name.to_string()),
+        syn::Fields::Unnamed(f) => {
+            let args = f
+                .unnamed
+                .iter()
+                .map(|x| ocaml_type(&x.ty, vars).map(|x| x.fmt_arg()))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(format!("{} of {}", name, args.join(" * ")))
+        }
+        syn::Fields::Named(f) => Ok(format!("{} of {}", name, record(f, vars, indent)?)),
+    }
+}
+
+fn body(t: &Derived) -> Result<String, Error> {
+    if let Some(s) = crate::sig_attr(&t.attrs)?.sig {
+        return Ok(s);
+    }
+
+    let params = t
+        .generics
+        .type_params()
+        .map(|x| x.ident.to_string())
+        .collect();
+    let mut vars = Vars::with_generics(params);
+
+    let variants = match &t.variants {
+        Some(v) => v,
+        None => {
+            let attrs = ocaml_attrs(&t.attrs);
+            let arity = t.data.len();
+            if attrs.unboxed && arity != 1 {
+                return Err(error(
+                    &t.ident,
+                    "unboxed types must contain exactly one field",
+                ));
+            }
+
+            if attrs.floats {
+                let all_floats = t.data.iter().all(|x| {
+                    matches!(ocaml_type(&x.ty, &mut Vars::default()), Ok(Type::Name(ref n)) if n == "float")
+                });
+                if !all_floats {
+                    return Err(error(&t.ident, "float arrays must only contain floats"));
+                }
+            }
+
+            let unboxed = if attrs.unboxed { " [@@unboxed]" } else { "" };
+            return match &t.data {
+                syn::Fields::Named(f) if !f.named.is_empty() => {
+                    Ok(format!("{}{}", record(f, &mut vars, "")?, unboxed))
+                }
+                fields => Ok(format!(
+                    "{}{}",
+                    constructor(&t.ident, fields, &mut vars, "")?,
+                    unboxed
+                )),
+            };
+        }
+    };
+
+    let mut dest = String::new();
+    for variant in variants {
+        let attrs = ocaml_attrs(&variant.attrs);
+        if attrs.unboxed || attrs.floats {
+            return Err(error(
+                &t.ident,
+                "enums cannot contain unboxed or float array variants",
+            ));
+        }
+        dest.push_str("\n  | ");
+        dest.push_str(&constructor(
+            &variant.ident,
+            &variant.fields,
+            &mut vars,
+            "    ",
+        )?);
+    }
+    Ok(dest)
+}
+
+fn head(t: &Derived) -> String {
+    let params: Vec<_> = t
+        .generics
+        .type_params()
+        .map(|x| type_var(&x.ident.to_string()))
+        .collect();
+    match params.len() {
+        0 => t.name(),
+        1 => format!("{} {}", params[0], t.name()),
+        _ => format!("({}) {}", params.join(", "), t.name()),
+    }
+}
+
+/// Generate a single recursive type declaration for all derived types
+pub fn type_decls(types: &[Derived]) -> Result<String, Error> {
+    let mut dest = String::new();
+    for (i, t) in types.iter().enumerate() {
+        let keyword = if i == 0 { "type" } else { "and" };
+        let body = body(t)?;
+        let sep = if body.starts_with('\n') { "" } else { " " };
+        dest.push_str(&format!("{} {} ={}{}\n", keyword, head(t), sep, body));
+    }
+    Ok(dest)
+}
@@ -4,6 +4,7 @@ use quote::quote;
 struct Attrs {
     unboxed: bool,
     floats: bool,
+    name: Option<String>,
 }
 
 fn is_ocaml(path: &syn::Path) -> bool {
@@ -45,6 +46,13 @@ fn variant_attrs(attrs: &[syn::Attribute]) -> Attrs {
                                 acc
                             }
                         }
+                        syn::NestedMeta::Meta(syn::Meta::NameValue(ref nv)) if nv.path.is_ident("name") => {
+                            match nv.lit {
+                                syn::Lit::Str(ref name) => acc.name = Some(name.value()),
+                                _ => panic!("ocaml name attribute must be a string"),
+                            }
+                            acc
+                        }
                      _ => panic!("unexpected ocaml attribute parameter"),
                     })
             } else {
@@ -166,7 +174,7 @@ pub fn fromvalue_derive(s: synstructure::Structure) -> proc_macro::TokenStream {
             )
         }
     });
-    if attrs.unboxed {
+    let from_value = if attrs.unboxed {
         s.gen_impl(quote! {
             gen unsafe impl ocaml::FromValue for @Self {
                 fn from_value(value: ocaml::Value) -> Self {
@@ -174,7 +182,6 @@ pub fn fromvalue_derive(s: synstructure::Structure) -> proc_macro::TokenStream {
                 }
             }
         })
-        .into()
     } else {
         let tag = if !attrs.floats {
             quote!({ value.tag() })
@@ -197,6 +204,291 @@ pub fn fromvalue_derive(s: synstructure::Structure) -> proc_macro::TokenStream {
                     }
                 }
             }
-        }).into()
+        })
+    };
+
+    let try_from_value = try_fromvalue_derive(&s, &attrs);
+
+    quote!(#from_value #try_from_value).into()
+}
+
+fn try_fromvalue_derive(s: &synstructure::Structure, attrs: &Attrs) -> proc_macro2::TokenStream {
+    let mut unit_tag = 0u8;
+    let mut non_unit_tag = 0u8;
+
+    if attrs.unboxed {
+        let construct =
+            s.variants()[0].construct(|_, _| quote!(ocaml::TryFromValue::try_from_value(value)?));
+        return s.gen_impl(quote! {
+            gen unsafe impl ocaml::TryFromValue for @Self {
+                fn try_from_value(value: ocaml::Value) -> Result<Self, ocaml::Error> {
+                    Ok(#construct)
+                }
+            }
+        });
+    }
+
+    if attrs.floats {
+        let arity = s.variants()[0].bindings().len();
+        return s.gen_impl(quote! {
+            gen unsafe impl ocaml::TryFromValue for @Self {
+                fn try_from_value(value: ocaml::Value) -> Result<Self, ocaml::Error> {
+                    if !value.is_block() {
+                        return Err(ocaml::ConversionError::ExpectedBlock.into());
+                    }
+                    if value.tag() != ocaml::Tag::DOUBLE_ARRAY {
+                        return Err(ocaml::ConversionError::InvalidTag {
+                            expected: ocaml::Tag::DOUBLE_ARRAY,
+                            found: value.tag(),
+                        }.into());
+                    }
+                    if value.size() != #arity {
+                        return Err(ocaml::ConversionError::InvalidSize {
+                            expected: #arity,
+                            found: value.size(),
+                        }.into());
+                    }
+                    Ok(ocaml::FromValue::from_value(value))
+                }
+            }
+        });
+    }
+
+    let body = s.variants().iter().map(|variant| {
+        let arity = variant.bindings().len();
+        let tag_ref = if arity > 0 {
+            &mut non_unit_tag
+        } else {
+            &mut unit_tag
+        };
+        let tag = proc_macro2::Literal::u8_unsuffixed(*tag_ref);
+        *tag_ref += 1;
+        let is_block = arity != 0;
+        let construct = variant
+            .construct(|_, idx| quote!(ocaml::TryFromValue::try_from_value(value.field(#idx))?));
+        let check_size = if is_block {
+            quote! {
+                if value.size() != #arity {
+                    return Err(ocaml::ConversionError::InvalidSize {
+                        expected: #arity,
+                        found: value.size(),
+                    }.into());
+                }
+            }
+        } else {
+            quote!()
+        };
+        quote!((#is_block, #tag) => {
+            #check_size
+            Ok(#construct)
+        })
+    });
+
+    s.gen_impl(quote! {
+        gen unsafe impl ocaml::TryFromValue for @Self {
+            fn try_from_value(value: ocaml::Value) -> Result<Self, ocaml::Error> {
+                let is_block = value.is_block();
+                if is_block && value.tag().0 >= ocaml::Tag::NO_SCAN.0 {
+                    return Err(ocaml::ConversionError::InvalidTag {
+                        expected: ocaml::Tag(0),
+                        found: value.tag(),
+                    }.into());
+                }
+                let tag = if !is_block { value.int_val() } else { value.tag().0 as isize };
+                match (is_block, tag) {
+                    #(#body),*
+                    _ => Err(ocaml::ConversionError::UnknownVariant.into()),
+                }
+            }
+        }
+    })
+}
+
+pub fn exception_derive(mut s: synstructure::Structure) -> proc_macro::TokenStream {
+    if let syn::Data::Struct(_) = s.ast().data {
+        panic!("ocaml can only derive Exception for enums")
+    }
+
+    let ident = &s.ast().ident;
+    let names: Vec<_> = s
+        .variants()
+        .iter()
+        .map(|variant| {
+            let variant_ident = variant.ast().ident;
+            let name = variant_attrs(variant.ast().attrs)
+                .name
+                .unwrap_or_else(|| variant_ident.to_string());
+            quote!(#ident::#variant_ident { .. } => #name)
+        })
+        .collect();
+
+    let body = s.variants_mut().to_vec().into_iter().map(|mut variant| {
+        let arity = variant.bindings().len();
+        for b in variant.bindings_mut() {
+            b.style = synstructure::BindStyle::Move;
+        }
+        if arity == 0 {
+            variant.fold(quote!(value = exn), |_, _| quote!())
+        } else {
+            let size = arity + 1;
+            let ghost = (1..size).map(|idx| quote!(value.store_field(#idx, ocaml::Value::unit())));
+            let init = quote!(
+                value = ocaml::Value::alloc(#size, ocaml::Tag(0));
+                value.store_field(0, exn);
+                #(#ghost);*;
+            );
+            let mut idx = 1usize;
+            variant.fold(init, |acc, b| {
+                let i = idx;
+                idx += 1;
+                quote!(#acc value.store_field(#i, ocaml::ToValue::to_value(#b));)
+            })
+        }
+    });
+
+    let (impl_generics, ty_generics, where_clause) = s.ast().generics.split_for_impl();
+    quote!(
+        impl #impl_generics ocaml::IntoException for #ident #ty_generics #where_clause {
+            fn into_exception(self) -> ocaml::Error {
+                let x = self;
+                let name = match &x {
+                    #(#names),*
+                };
+                let named: ocaml::Value = match ocaml::Value::named(name) {
+                    Some(named) => named,
+                    None => return ocaml::Error::not_registered(name),
+                };
+                unsafe {
+                    ocaml::frame!((value, exn) {
+                        exn = named;
+                        match x {
+                            #(#body),*
+                        }
+                        ocaml::CamlError::Exception(value, None).into()
+                    })
+                }
+            }
+        }
+    )
+    .into()
+}
+
+#[derive(Default)]
+struct CustomAttrs {
+    compare: bool,
+    compare_ext: bool,
+    hash: bool,
+    serialize: bool,
+    borrow_checked: bool,
+    name: Option<String>,
+    heap_size: Option<syn::Path>,
+}
+
+fn custom_attrs(attrs: &[syn::Attribute]) -> CustomAttrs {
+    let mut acc = CustomAttrs::default();
+    for attr in attrs.iter().filter(|attr| is_ocaml(&attr.path)) {
+        let list = match attr.parse_meta() {
+            Ok(syn::Meta::List(list)) => list,
+            _ => panic!("ocaml attribute must take a list of valid attributes in parentheses"),
+        };
+        for meta in list.nested.iter() {
+            match meta {
+                syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("compare") => {
+                    acc.compare = true
+                }
+                syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("compare_ext") => {
+                    acc.compare_ext = true
+                }
+                syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("hash") => {
+                    acc.hash = true
+                }
+                syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("serialize") => {
+                    acc.serialize = true
+                }
+                syn::NestedMeta::Meta(syn::Meta::Path(path))
+                    if path.is_ident("borrow_checked") =>
+                {
+                    acc.borrow_checked = true
+                }
+                syn::NestedMeta::Meta(syn::Meta::NameValue(nv)) if nv.path.is_ident("name") => {
+                    match nv.lit {
+                        syn::Lit::Str(ref name) => acc.name = Some(name.value()),
+                        _ => panic!("ocaml name attribute must be a string"),
+                    }
+                }
+                syn::NestedMeta::Meta(syn::Meta::NameValue(nv))
+                    if nv.path.is_ident("heap_size") =>
+                {
+                    match nv.lit {
+                        syn::Lit::Str(ref f) => {
+                            acc.heap_size = Some(f.parse().expect(
+                                "ocaml heap_size attribute must be the path of a function",
+                            ))
+                        }
+                        _ => panic!("ocaml heap_size attribute must be a string"),
+                    }
+                }
+                _ => panic!("unexpected ocaml attribute parameter, expected one of compare, compare_ext, hash, serialize, borrow_checked, name or heap_size"),
+            }
+        }
+    }
+    acc
+}
+
+pub fn custom_derive(s: synstructure::Structure) -> proc_macro::TokenStream {
+    // Every instantiation of a generic type would share the same identifier, so a block created
+    // for one could be converted to a `Pointer` of another
+    if !s.ast().generics.params.is_empty() {
+        panic!("ocaml can't derive Custom for generic types, implement Custom for each instantiation with a different NAME instead")
+    }
+
+    let attrs = custom_attrs(&s.ast().attrs);
+    let ident = &s.ast().ident;
+
+    let name = match attrs.name {
+        Some(name) => quote!(concat!(#name, "\0")),
+        None => quote!(concat!(module_path!(), "::", stringify!(#ident), "\0")),
+    };
+
+    let mut ops = vec![quote!(finalize: Some(ocaml::custom::finalize::<Self>))];
+    if attrs.compare {
+        ops.push(quote!(compare: Some(ocaml::custom::compare::<Self>)));
+    }
+    if attrs.compare_ext {
+        ops.push(quote!(compare_ext: Some(ocaml::custom::compare_ext::<Self>)));
+    }
+    if attrs.hash {
+        ops.push(quote!(hash: Some(ocaml::custom::hash::<Self>)));
     }
+    if attrs.serialize {
+        ops.push(quote!(serialize: Some(ocaml::custom::serialize::<Self>)));
+        ops.push(quote!(deserialize: Some(ocaml::custom::deserialize::<Self>)));
+    }
+
+    let borrow_checked = attrs.borrow_checked;
+
+    let heap_size = attrs.heap_size.map(|f| {
+        quote!(
+            fn heap_size(&self) -> usize {
+                #f(self)
+            }
+        )
+    });
+
+    quote!(
+        impl ocaml::Custom for #ident {
+            const NAME: &'static str = #name;
+
+            const BORROW_CHECKED: bool = #borrow_checked;
+
+            const OPS: ocaml::custom::CustomOps = ocaml::custom::CustomOps {
+                identifier: Self::NAME.as_ptr() as *const ocaml::sys::Char,
+                #(#ops,)*
+                ..ocaml::custom::DEFAULT_CUSTOM_OPS
+            };
+
+            #heap_size
+        }
+    )
+    .into()
 }
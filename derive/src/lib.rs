@@ -29,13 +29,103 @@ fn check_func(item_fn: &mut syn::ItemFn) {
     });
 }
 
+fn is_runtime(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Reference(r) if r.mutability.is_some() => match r.elem.as_ref() {
+            syn::Type::Path(p) => p
+                .path
+                .segments
+                .last()
+                .map_or(false, |x| x.ident == "Runtime"),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Replaces lifetimes with `'_`, since the generated function doesn't declare the lifetimes used
+/// by the original function
+fn elide_lifetimes(tokens: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    let mut dest = proc_macro2::TokenStream::new();
+    let mut iter = tokens.into_iter();
+    while let Some(tt) = iter.next() {
+        match tt {
+            proc_macro2::TokenTree::Punct(p) if p.as_char() == '\'' => {
+                iter.next();
+                dest.extend(quote!('_));
+            }
+            proc_macro2::TokenTree::Group(g) => {
+                let mut group = proc_macro2::Group::new(g.delimiter(), elide_lifetimes(g.stream()));
+                group.set_span(g.span());
+                dest.extend(Some(proc_macro2::TokenTree::Group(group)));
+            }
+            tt => dest.extend(Some(tt)),
+        }
+    }
+    dest
+}
+
+fn arg_types(item_fn: &syn::ItemFn) -> Vec<proc_macro2::TokenStream> {
+    item_fn
+        .sig
+        .inputs
+        .iter()
+        .map(|arg| match arg {
+            syn::FnArg::Typed(t) => {
+                let ty = &t.ty;
+                elide_lifetimes(quote!(#ty))
+            }
+            syn::FnArg::Receiver(_) => quote!(ocaml::Value),
+        })
+        .collect()
+}
+
+/// Converts an argument using `TryFromValue`, `Pointer<T>` is also accepted without checking when
+/// `T` doesn't implement `Custom`
+fn convert_arg(
+    ty: &proc_macro2::TokenStream,
+    value: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    quote! {
+        {
+            use ocaml::{FuncArgPointer as _, FuncArgValue as _};
+            (&ocaml::FuncArg::<#ty>(#value, core::marker::PhantomData)).func_arg()
+        }
+    }
+}
+
+/// Stores the runtime handle in a local variable, so values borrowed from it can't outlive the
+/// generated function
+fn recover_runtime(ident: &syn::Ident) -> proc_macro2::TokenStream {
+    let handle = syn::Ident::new(&format!("__ocaml_runtime_{}", ident), ident.span());
+    quote! {
+        let mut #handle = unsafe { ocaml::Runtime::recover_handle() };
+        let #ident = &mut #handle;
+    }
+}
+
+fn runtime_args(item_fn: &syn::ItemFn) -> Vec<bool> {
+    item_fn
+        .sig
+        .inputs
+        .iter()
+        .map(|arg| match arg {
+            syn::FnArg::Typed(t) => is_runtime(&t.ty),
+            syn::FnArg::Receiver(_) => false,
+        })
+        .collect()
+}
+
 /// `func` is used export Rust functions to OCaml, performing the necessary wrapping/unwrapping
 /// automatically.
 ///
 /// - Wraps the function body using `ocaml::body`
 /// - Automatic type conversion for arguments/return value (including Result types)
+/// - Arguments are checked using `TryFromValue`, `Invalid_argument` is raised when a check fails
 /// - Defines a bytecode function automatically for functions that take more than 5 arguments. The
 /// bytecode function for `my_func` would be `my_func_bytecode`
+/// - Arguments with the type `&mut ocaml::Runtime` receive the runtime handle and are not
+///   passed from OCaml
 #[proc_macro_attribute]
 pub fn ocaml_func(_attribute: TokenStream, item: TokenStream) -> TokenStream {
     let mut item_fn: syn::ItemFn = syn::parse(item).unwrap();
@@ -65,9 +155,13 @@ pub fn ocaml_func(_attribute: TokenStream, item: TokenStream) -> TokenStream {
         })
         .collect();
 
+    let runtime = runtime_args(&item_fn);
+
     let mut ocaml_args: Vec<_> = args
         .iter()
-        .map(|t| match t {
+        .zip(runtime.iter())
+        .filter(|(_, rt)| !**rt)
+        .map(|(t, _)| match t {
             Some(ident) => {
                 let ident = &ident.ident;
                 quote! { #ident: ocaml::Value }
@@ -84,12 +178,34 @@ pub fn ocaml_func(_attribute: TokenStream, item: TokenStream) -> TokenStream {
         })
         .collect();
 
+    let ocaml_param_names: syn::punctuated::Punctuated<syn::Ident, syn::token::Comma> = args
+        .iter()
+        .zip(runtime.iter())
+        .filter_map(|(arg, rt)| match arg {
+            Some(ident) if !rt => Some(ident.ident.clone()),
+            _ => None,
+        })
+        .collect();
+
+    let types = arg_types(&item_fn);
     let convert_params: Vec<_> = args
         .iter()
-        .filter_map(|arg| match arg {
+        .zip(runtime.iter())
+        .zip(types.iter())
+        .filter_map(|((arg, rt), ty)| match arg {
+            Some(ident) if *rt => {
+                let ident = ident.ident.clone();
+                Some(recover_runtime(&ident))
+            }
             Some(ident) => {
                 let ident = ident.ident.clone();
-                Some(quote! { let #ident = ocaml::FromValue::from_value(#ident); })
+                let convert = convert_arg(ty, quote!(#ident));
+                Some(quote! {
+                    let #ident = match #convert {
+                        Ok(x) => x,
+                        Err(e) => ocaml::Error::raise_argument_error(stringify!(#ident), e),
+                    };
+                })
             }
             None => None,
         })
@@ -148,11 +264,14 @@ pub fn ocaml_func(_attribute: TokenStream, item: TokenStream) -> TokenStream {
             #attr
         )*
         pub #constness #unsafety extern "C" fn #name(#(#ocaml_args),*) -> ocaml::Value #where_clause {
-            ocaml::body!((#param_names) {
+            ocaml::body!((#ocaml_param_names) {
                 #inner
                 #(#convert_params);*
                 let res = inner(#param_names);
-                ocaml::ToValue::to_value(res)
+                {
+                    use ocaml::{FuncReturnException as _, FuncReturnValue as _};
+                    (&mut ocaml::FuncReturn(Some(res))).func_return()
+                }
             })
         }
     };
@@ -318,9 +437,13 @@ fn ocaml_bytecode_func_impl(
         })
         .collect();
 
+    let runtime = runtime_args(&item_fn);
+
     let mut ocaml_args: Vec<_> = args
         .iter()
-        .map(|t| match t {
+        .zip(runtime.iter())
+        .filter(|(_, rt)| !**rt)
+        .map(|(t, _)| match t {
             Some(ident) => {
                 quote! { #ident: ocaml::Value }
             }
@@ -328,14 +451,29 @@ fn ocaml_bytecode_func_impl(
         })
         .collect();
 
+    // When calling the native function the runtime handle isn't passed
     let mut param_names: syn::punctuated::Punctuated<syn::Ident, syn::token::Comma> = args
         .iter()
-        .filter_map(|arg| match arg {
+        .zip(runtime.iter())
+        .filter_map(|(arg, rt)| match arg {
+            Some(_) if *rt && original.is_some() => None,
             Some(ident) => Some(ident.ident.clone()),
             None => None,
         })
         .collect();
 
+    let runtime_params: Vec<_> = args
+        .iter()
+        .zip(runtime.iter())
+        .filter_map(|(arg, rt)| match arg {
+            Some(ident) if *rt && original.is_none() => {
+                let ident = ident.ident.clone();
+                Some(recover_runtime(&ident))
+            }
+            _ => None,
+        })
+        .collect();
+
     if ocaml_args.is_empty() {
         ocaml_args.push(quote! { _unit: ocaml::Value});
         param_names.push(syn::Ident::new("__ocaml_unit", name.span()));
@@ -402,17 +540,40 @@ fn ocaml_bytecode_func_impl(
 
     let len = ocaml_args.len();
 
+    // When wrapping the native function the arguments are passed along as `Value`
+    let types: Vec<_> = match original {
+        Some(_) => item_fn
+            .sig
+            .inputs
+            .iter()
+            .map(|_| quote!(ocaml::Value))
+            .collect(),
+        None => arg_types(&item_fn),
+    };
+
     if len > 5 {
         let convert_params: Vec<_> = args
             .iter()
-            .filter_map(|arg| match arg {
-                Some(ident) => Some(quote! {
-                    #[allow(clippy::not_unsafe_ptr_arg_deref)]
-                    let #ident = ocaml::FromValue::from_value(unsafe {
-                        core::ptr::read(__ocaml_argv.add(__ocaml_arg_index as usize))
-                    });
-                    __ocaml_arg_index += 1 ;
-                }),
+            .zip(runtime.iter())
+            .zip(types.iter())
+            .filter_map(|((arg, rt), ty)| match arg {
+                Some(_) if *rt => None,
+                Some(ident) => {
+                    let convert = convert_arg(
+                        ty,
+                        quote!(unsafe {
+                            core::ptr::read(__ocaml_argv.add(__ocaml_arg_index as usize))
+                        }),
+                    );
+                    Some(quote! {
+                        #[allow(clippy::not_unsafe_ptr_arg_deref)]
+                        let #ident = match #convert {
+                            Ok(x) => x,
+                            Err(e) => ocaml::Error::raise_argument_error(stringify!(#ident), e),
+                        };
+                        __ocaml_arg_index += 1 ;
+                    })
+                }
                 None => None,
             })
             .collect();
@@ -428,17 +589,30 @@ fn ocaml_bytecode_func_impl(
 
                 let mut __ocaml_arg_index = 0;
                 #(#convert_params);*
+                #(#runtime_params);*
                 let res = inner(#param_names);
-                ocaml::ToValue::to_value(res)
+                {
+                    use ocaml::{FuncReturnException as _, FuncReturnValue as _};
+                    (&mut ocaml::FuncReturn(Some(res))).func_return()
+                }
             }
         }
     } else {
         let convert_params: Vec<_> = args
             .iter()
-            .filter_map(|arg| match arg {
+            .zip(runtime.iter())
+            .zip(types.iter())
+            .filter_map(|((arg, rt), ty)| match arg {
+                Some(_) if *rt => None,
                 Some(ident) => {
                     let ident = ident.ident.clone();
-                    Some(quote! { let #ident = ocaml::FromValue::from_value(#ident); })
+                    let convert = convert_arg(ty, quote!(#ident));
+                    Some(quote! {
+                        let #ident = match #convert {
+                            Ok(x) => x,
+                            Err(e) => ocaml::Error::raise_argument_error(stringify!(#ident), e),
+                        };
+                    })
                 }
                 None => None,
             })
@@ -452,12 +626,45 @@ fn ocaml_bytecode_func_impl(
                 #inner
 
                 #(#convert_params);*
+                #(#runtime_params);*
                 let res = inner(#param_names);
-                ocaml::ToValue::to_value(res)
+                {
+                    use ocaml::{FuncReturnException as _, FuncReturnValue as _};
+                    (&mut ocaml::FuncReturn(Some(res))).func_return()
+                }
             }
         }
     }
 }
 
+/// `sig` is used to specify the OCaml type of a function or type when generating OCaml code
+/// using `ocaml-build`. It has no effect on the generated Rust code.
+///
+/// ```rust,ignore
+/// #[ocaml::native_func]
+/// #[ocaml::sig("int -> int")]
+/// pub fn incr(value: ocaml::Value) -> ocaml::Value {
+///     ocaml::Value::int(value.int_val() + 1)
+/// }
+/// ```
+///
+/// `noalloc` marks the external `[@@noalloc]`, this should only be used for functions that never
+/// allocate, raise an exception or call back into OCaml: `#[ocaml::sig("float -> float", noalloc)]`
+#[proc_macro_attribute]
+pub fn ocaml_sig(attribute: TokenStream, item: TokenStream) -> TokenStream {
+    let args = syn::parse_macro_input!(attribute as syn::AttributeArgs);
+    let mut sig = false;
+    for arg in args {
+        match arg {
+            syn::NestedMeta::Lit(syn::Lit::Str(_)) if !sig => sig = true,
+            syn::NestedMeta::Meta(syn::Meta::Path(p)) if p.is_ident("noalloc") => (),
+            _ => panic!("ocaml::sig expects a string argument and/or noalloc"),
+        }
+    }
+    item
+}
+
 synstructure::decl_derive!([ToValue, attributes(ocaml)] => derive::tovalue_derive);
 synstructure::decl_derive!([FromValue, attributes(ocaml)] => derive::fromvalue_derive);
+synstructure::decl_derive!([Exception, attributes(ocaml)] => derive::exception_derive);
+synstructure::decl_derive!([Custom, attributes(ocaml)] => derive::custom_derive);
@@ -0,0 +1,96 @@
+use crate::{Custom, Error, Fn1, FromValue, Pointer, ToValue, Value};
+
+use alloc::boxed::Box;
+use core::any::Any;
+use core::cell::RefCell;
+
+type Closure = RefCell<dyn Any>;
+
+/// Rust closure stored in a custom block, the closure itself is boxed so it doesn't move when the
+/// garbage collector moves the block
+struct ClosureState {
+    f: Box<Closure>,
+    call: unsafe fn(&Closure, Value) -> Value,
+}
+
+unsafe extern "C" fn finalize_closure(v: Value) {
+    let p: Pointer<ClosureState> = Pointer::from_value_unchecked(v);
+    p.drop_in_place()
+}
+
+impl Custom for ClosureState {
+    crate::custom! {
+        name: "ocaml-rs.closure",
+        finalize: finalize_closure
+    }
+}
+
+unsafe fn call_closure<A: FromValue, R: ToValue, F: 'static + FnMut(A) -> R>(
+    f: &Closure,
+    arg: Value,
+) -> Value {
+    // Calling the same closure again before it returns would alias the `FnMut`
+    let result = match f.try_borrow_mut() {
+        Ok(mut f) => match f.downcast_mut::<F>() {
+            Some(f) => Ok(f(A::from_value(arg))),
+            None => Err(Error::Message("invalid closure type".into())),
+        },
+        Err(_) => Err(Error::ReentrantClosure),
+    };
+
+    // The borrow is released before converting the result, raising an exception skips destructors
+    match result {
+        Ok(x) => x.to_value(),
+        Err(e) => Err::<Value, Error>(e).to_value(),
+    }
+}
+
+/// Called by OCaml closures created using `Fn1::new`, this should not be called directly
+#[doc(hidden)]
+#[no_mangle]
+pub unsafe extern "C" fn ocaml_rs_closure_call(state: Value, arg: Value) -> Value {
+    crate::body!((state, arg) {
+        let p: Pointer<ClosureState> = Pointer::from_value_unchecked(state);
+        let f = &*p.as_ref().f as *const Closure;
+        let call = p.as_ref().call;
+        call(&*f, arg)
+    })
+}
+
+impl<A: 'static + FromValue, R: 'static + ToValue> Fn1<A, R> {
+    /// Convert a Rust closure to an OCaml closure, the Rust closure is dropped when the OCaml
+    /// value is garbage collected.
+    ///
+    /// This requires a small amount of OCaml code to create the closure, which should be included
+    /// in the OCaml library:
+    ///
+    /// ```ocaml
+    /// external closure_call : 'state -> 'a -> 'b = "ocaml_rs_closure_call"
+    /// let () = Callback.register "ocaml_rs_closure" (fun state -> closure_call state)
+    /// ```
+    ///
+    /// Without it `Fn1::new` returns an error. Closures with several arguments can take a tuple.
+    ///
+    /// Calling the closure again before it returns fails with `Error::ReentrantClosure`, which is
+    /// raised as `Failure`
+    pub fn new<F: 'static + FnMut(A) -> R>(f: F) -> Result<Fn1<A, R>, Error> {
+        let state = Pointer::alloc_custom(ClosureState {
+            f: Box::new(RefCell::new(f)),
+            call: call_closure::<A, R, F>,
+        });
+
+        // The named value is loaded after allocating, since it could be moved by the GC
+        let make: Value = match Value::named("ocaml_rs_closure") {
+            Some(x) => x,
+            None => {
+                return Err(Error::Message(
+                    "ocaml_rs_closure has not been registered with the OCaml runtime, \
+                     see the documentation for ocaml::Fn1::new"
+                        .into(),
+                ))
+            }
+        };
+        let value = make.call(state)?;
+        Ok(Fn1::from_value(value))
+    }
+}
@@ -1,8 +1,88 @@
 use crate::{
-    value::{FromValue, ToValue, Value},
+    error::{ConversionError, Error},
+    value::{FromValue, ToValue, TryFromValue, Value},
     Tag,
 };
 
+pub(crate) fn check_immediate(v: Value) -> Result<(), Error> {
+    if v.is_block() {
+        return Err(ConversionError::ExpectedImmediate.into());
+    }
+    Ok(())
+}
+
+pub(crate) fn check_block(v: Value, tag: Tag) -> Result<(), Error> {
+    if !v.is_block() {
+        return Err(ConversionError::ExpectedBlock.into());
+    }
+
+    let found = v.tag();
+    if found != tag {
+        return Err(ConversionError::InvalidTag {
+            expected: tag,
+            found,
+        }
+        .into());
+    }
+    Ok(())
+}
+
+pub(crate) fn check_size(v: Value, size: usize) -> Result<(), Error> {
+    let found = v.size();
+    if found != size {
+        return Err(ConversionError::InvalidSize {
+            expected: size,
+            found,
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Returns the identifier of a custom block's `custom_operations`
+pub(crate) fn custom_identifier(v: Value) -> &'static str {
+    unsafe {
+        let ops = *(v.0 as *const *const crate::sys::custom_operations);
+        let ptr = (*ops).identifier as *const u8;
+        let mut len = 0;
+        while *ptr.add(len) != 0 {
+            len += 1;
+        }
+        core::str::from_utf8(core::slice::from_raw_parts(ptr, len)).unwrap_or("<invalid>")
+    }
+}
+
+pub(crate) fn check_custom(v: Value, identifier: &'static str) -> Result<(), Error> {
+    check_block(v, Tag::CUSTOM)?;
+    let found = custom_identifier(v);
+    if found != identifier {
+        return Err(ConversionError::InvalidCustom {
+            expected: identifier,
+            found,
+        }
+        .into());
+    }
+    Ok(())
+}
+
+pub(crate) fn check_list(mut v: Value) -> Result<(), Error> {
+    while v.0 != crate::sys::EMPTY_LIST {
+        check_block(v, Tag(crate::sys::TAG_CONS))?;
+        check_size(v, 2)?;
+        v = v.field(1);
+    }
+    Ok(())
+}
+
+pub(crate) fn check_str(value: Value) -> Result<&'static mut [u8], Error> {
+    check_block(value, Tag::STRING)?;
+    unsafe {
+        let len = crate::sys::caml_string_length(value.0);
+        let ptr = crate::sys::string_val(value.0);
+        Ok(::core::slice::from_raw_parts_mut(ptr, len))
+    }
+}
+
 macro_rules! value_i {
     ($t:ty) => {
         unsafe impl ToValue for $t {
@@ -16,6 +96,13 @@ macro_rules! value_i {
                 v.int_val() as $t
             }
         }
+
+        unsafe impl TryFromValue for $t {
+            fn try_from_value(v: $crate::Value) -> Result<$t, Error> {
+                check_immediate(v)?;
+                Ok(v.int_val() as $t)
+            }
+        }
     };
     ($($t:ty),*) => {
         $(value_i!($t);)*
@@ -35,6 +122,13 @@ macro_rules! value_f {
                 v.float_val() as $t
             }
         }
+
+        unsafe impl TryFromValue for $t {
+            fn try_from_value(v: $crate::Value) -> Result<$t, Error> {
+                check_block(v, Tag::DOUBLE)?;
+                Ok(v.float_val() as $t)
+            }
+        }
     };
     ($($t:ty),*) => {
         $(value_f!($t);)*
@@ -56,6 +150,13 @@ unsafe impl FromValue for i64 {
     }
 }
 
+unsafe impl TryFromValue for i64 {
+    fn try_from_value(v: crate::Value) -> Result<i64, Error> {
+        check_custom(v, "_j")?;
+        Ok(v.int64_val())
+    }
+}
+
 unsafe impl ToValue for u64 {
     fn to_value(self) -> crate::Value {
         Value::int64(self as i64)
@@ -68,6 +169,13 @@ unsafe impl FromValue for u64 {
     }
 }
 
+unsafe impl TryFromValue for u64 {
+    fn try_from_value(v: crate::Value) -> Result<u64, Error> {
+        check_custom(v, "_j")?;
+        Ok(v.int64_val() as u64)
+    }
+}
+
 unsafe impl ToValue for i32 {
     fn to_value(self) -> crate::Value {
         Value::int32(self)
@@ -80,9 +188,16 @@ unsafe impl FromValue for i32 {
     }
 }
 
+unsafe impl TryFromValue for i32 {
+    fn try_from_value(v: crate::Value) -> Result<i32, Error> {
+        check_custom(v, "_i")?;
+        Ok(v.int32_val())
+    }
+}
+
 unsafe impl ToValue for u32 {
     fn to_value(self) -> crate::Value {
-        Value::int64(self as i64)
+        Value::int32(self as i32)
     }
 }
 
@@ -92,6 +207,13 @@ unsafe impl FromValue for u32 {
     }
 }
 
+unsafe impl TryFromValue for u32 {
+    fn try_from_value(v: crate::Value) -> Result<u32, Error> {
+        check_custom(v, "_i")?;
+        Ok(v.int32_val() as u32)
+    }
+}
+
 struct Incr(usize);
 
 impl Incr {
@@ -116,6 +238,30 @@ macro_rules! tuple_impl {
             }
         }
 
+        unsafe impl<$($t: TryFromValue),*> TryFromValue for ($($t,)*) {
+            fn try_from_value(v: crate::Value) -> Result<($($t,)*), Error> {
+                #[allow(unused)]
+                let mut len = 0;
+                $(
+                    #[allow(unused)]
+                    {
+                        len = $n + 1;
+                    }
+                )*
+
+                check_block(v, Tag(0))?;
+                check_size(v, len)?;
+
+                let mut i = Incr(0);
+                #[allow(unused)]
+                Ok((
+                    $(
+                        $t::try_from_value(v.field(i.get()))?,
+                    )*
+                ))
+            }
+        }
+
         unsafe impl<$($t: ToValue),*> ToValue for ($($t,)*) {
             fn to_value(self) -> crate::Value {
                 #[allow(unused)]
@@ -174,6 +320,13 @@ unsafe impl FromValue for bool {
     }
 }
 
+unsafe impl TryFromValue for bool {
+    fn try_from_value(v: Value) -> Result<bool, Error> {
+        check_immediate(v)?;
+        Ok(v.int_val() != 0)
+    }
+}
+
 #[cfg(not(feature = "no-std"))]
 unsafe impl ToValue for String {
     fn to_value(self) -> Value {
@@ -200,6 +353,17 @@ unsafe impl FromValue for String {
     }
 }
 
+#[cfg(not(feature = "no-std"))]
+unsafe impl TryFromValue for String {
+    fn try_from_value(value: Value) -> Result<String, Error> {
+        let s = check_str(value)?;
+        match ::std::str::from_utf8(s) {
+            Ok(s) => Ok(s.into()),
+            Err(_) => Err(ConversionError::InvalidUtf8.into()),
+        }
+    }
+}
+
 unsafe impl ToValue for () {
     fn to_value(self) -> Value {
         Value::unit()
@@ -216,6 +380,22 @@ unsafe impl<T: FromValue> FromValue for Option<T> {
     }
 }
 
+unsafe impl<T: TryFromValue> TryFromValue for Option<T> {
+    fn try_from_value(value: Value) -> Result<Option<T>, Error> {
+        if value == Value::none() {
+            return Ok(None);
+        }
+
+        if value.is_long() {
+            return Err(ConversionError::UnknownVariant.into());
+        }
+
+        check_block(value, Tag(crate::sys::TAG_SOME))?;
+        check_size(value, 1)?;
+        Ok(Some(T::try_from_value(value.field(0))?))
+    }
+}
+
 unsafe impl<T: ToValue> ToValue for Option<T> {
     fn to_value(self) -> Value {
         match self {
@@ -239,6 +419,15 @@ unsafe impl FromValue for &str {
     }
 }
 
+unsafe impl TryFromValue for &str {
+    fn try_from_value(value: Value) -> Result<Self, Error> {
+        match ::core::str::from_utf8(check_str(value)?) {
+            Ok(s) => Ok(s),
+            Err(_) => Err(ConversionError::InvalidUtf8.into()),
+        }
+    }
+}
+
 unsafe impl ToValue for &str {
     fn to_value(self) -> Value {
         frame!((value) {
@@ -263,6 +452,15 @@ unsafe impl FromValue for &mut str {
     }
 }
 
+unsafe impl TryFromValue for &mut str {
+    fn try_from_value(value: Value) -> Result<Self, Error> {
+        match ::core::str::from_utf8_mut(check_str(value)?) {
+            Ok(s) => Ok(s),
+            Err(_) => Err(ConversionError::InvalidUtf8.into()),
+        }
+    }
+}
+
 unsafe impl ToValue for &mut str {
     fn to_value(self) -> Value {
         frame!((value) {
@@ -284,6 +482,12 @@ unsafe impl FromValue for &[u8] {
     }
 }
 
+unsafe impl TryFromValue for &[u8] {
+    fn try_from_value(value: Value) -> Result<Self, Error> {
+        Ok(check_str(value)?)
+    }
+}
+
 unsafe impl ToValue for &[u8] {
     fn to_value(self) -> Value {
         frame!((value) {
@@ -305,6 +509,12 @@ unsafe impl FromValue for &mut [u8] {
     }
 }
 
+unsafe impl TryFromValue for &mut [u8] {
+    fn try_from_value(value: Value) -> Result<Self, Error> {
+        check_str(value)
+    }
+}
+
 unsafe impl ToValue for &mut [u8] {
     fn to_value(self) -> Value {
         frame!((value) {
@@ -349,6 +559,35 @@ unsafe impl<V: FromValue> FromValue for Vec<V> {
     }
 }
 
+#[cfg(not(feature = "no-std"))]
+unsafe impl<V: TryFromValue> TryFromValue for Vec<V> {
+    fn try_from_value(v: Value) -> Result<Vec<V>, Error> {
+        check_block(v, Tag(0))?;
+        let len = v.size();
+        let mut dst = Vec::with_capacity(len);
+        for i in 0..len {
+            dst.push(V::try_from_value(v.field(i))?)
+        }
+        Ok(dst)
+    }
+}
+
+fn check_scannable(value: Value) -> Result<(), Error> {
+    if !value.is_block() {
+        return Err(ConversionError::ExpectedBlock.into());
+    }
+
+    if value.tag() >= Tag::NO_SCAN {
+        return Err(ConversionError::InvalidTag {
+            expected: Tag(0),
+            found: value.tag(),
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
 unsafe impl<'a> FromValue for &'a [Value] {
     fn from_value(value: Value) -> &'a [Value] {
         unsafe {
@@ -360,6 +599,13 @@ unsafe impl<'a> FromValue for &'a [Value] {
     }
 }
 
+unsafe impl<'a> TryFromValue for &'a [Value] {
+    fn try_from_value(value: Value) -> Result<&'a [Value], Error> {
+        check_scannable(value)?;
+        Ok(FromValue::from_value(value))
+    }
+}
+
 unsafe impl<'a> FromValue for &'a mut [Value] {
     fn from_value(value: Value) -> &'a mut [Value] {
         unsafe {
@@ -371,6 +617,13 @@ unsafe impl<'a> FromValue for &'a mut [Value] {
     }
 }
 
+unsafe impl<'a> TryFromValue for &'a mut [Value] {
+    fn try_from_value(value: Value) -> Result<&'a mut [Value], Error> {
+        check_scannable(value)?;
+        Ok(FromValue::from_value(value))
+    }
+}
+
 #[cfg(not(feature = "no-std"))]
 unsafe impl<K: Ord + FromValue, V: FromValue> FromValue for std::collections::BTreeMap<K, V> {
     fn from_value(v: Value) -> std::collections::BTreeMap<K, V> {
@@ -387,6 +640,26 @@ unsafe impl<K: Ord + FromValue, V: FromValue> FromValue for std::collections::BT
     }
 }
 
+#[cfg(not(feature = "no-std"))]
+unsafe impl<K: Ord + TryFromValue, V: TryFromValue> TryFromValue
+    for std::collections::BTreeMap<K, V>
+{
+    fn try_from_value(v: Value) -> Result<std::collections::BTreeMap<K, V>, Error> {
+        check_list(v)?;
+
+        let mut dest = std::collections::BTreeMap::new();
+
+        let mut tmp = v;
+        while tmp.0 != crate::sys::EMPTY_LIST {
+            let (k, v) = TryFromValue::try_from_value(tmp.field(0))?;
+            dest.insert(k, v);
+            tmp = tmp.field(1);
+        }
+
+        Ok(dest)
+    }
+}
+
 #[cfg(not(feature = "no-std"))]
 unsafe impl<K: ToValue, V: ToValue> ToValue for std::collections::BTreeMap<K, V> {
     fn to_value(self) -> Value {
@@ -420,6 +693,23 @@ unsafe impl<T: FromValue> FromValue for std::collections::LinkedList<T> {
     }
 }
 
+#[cfg(not(feature = "no-std"))]
+unsafe impl<T: TryFromValue> TryFromValue for std::collections::LinkedList<T> {
+    fn try_from_value(v: Value) -> Result<std::collections::LinkedList<T>, Error> {
+        check_list(v)?;
+
+        let mut dest = std::collections::LinkedList::new();
+
+        let mut tmp = v;
+        while tmp.0 != crate::sys::EMPTY_LIST {
+            dest.push_back(T::try_from_value(tmp.field(0))?);
+            tmp = tmp.field(1);
+        }
+
+        Ok(dest)
+    }
+}
+
 #[cfg(not(feature = "no-std"))]
 unsafe impl<T: ToValue> ToValue for std::collections::LinkedList<T> {
     fn to_value(self) -> Value {
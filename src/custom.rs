@@ -37,6 +37,17 @@ impl Default for CustomOps {
 /// value. Once the Rust value is owned by OCaml it should be accessed using `ocaml::Pointer` to
 /// avoid reallocating the same value
 ///
+/// `#[derive(ocaml::Custom)]` can be used to implement `Custom` with operations based on Rust
+/// traits, the derived finalizer always drops the Rust value. The `ocaml` attribute accepts:
+///
+/// - `compare`, `compare_ext`, `hash` and `serialize`: use `Ord`, `PartialOrd<Int>`, `Hash` and
+///   `Serialize` for the matching operations
+/// - `borrow_checked`: set `BORROW_CHECKED`
+/// - `name = "..."`: the identifier, which defaults to the module path and type name
+/// - `heap_size = "path"`: a `fn(&Self) -> usize` used for `heap_size`
+///
+/// Generic types can't be derived, since every instantiation would have the same identifier
+///
 /// ```rust
 /// struct Example(ocaml::Int);
 ///
@@ -72,19 +83,277 @@ pub trait Custom {
     /// related to this custom type
     const MAX: usize = 1;
 
+    /// Store a borrow flag after the value so `Pointer::borrow` and `Pointer::borrow_mut` can be
+    /// used. This changes the size of the custom block, so it should only be enabled for values
+    /// allocated by `ocaml-rs` (`ToValue`, `Pointer::alloc_custom` or the `deserialize` operation
+    /// from `custom_serialize!`), not blocks created from C
+    const BORROW_CHECKED: bool = false;
+
+    /// Amount of memory owned by the value outside of the OCaml heap, in bytes. When this is
+    /// non-zero `ToValue` allocates the value using `caml_alloc_custom_mem` instead of `USED` and
+    /// `MAX`, so the garbage collector runs more often when values own large buffers
+    fn heap_size(&self) -> usize {
+        0
+    }
+
     /// Get a static reference the this type's `CustomOps` implementation
     fn ops() -> &'static CustomOps {
         &Self::OPS
     }
 }
 
+/// Borrow state of a custom value, this is stored after the value in custom blocks allocated for
+/// `Custom` types with `BORROW_CHECKED` set and used by `Pointer::borrow` and `Pointer::borrow_mut`
+pub(crate) type BorrowFlag = core::cell::Cell<isize>;
+
+/// Offset of the `BorrowFlag` in the data of a custom block containing a `T`
+pub(crate) const fn borrow_flag_offset<T>() -> usize {
+    let align = core::mem::align_of::<BorrowFlag>();
+    core::mem::size_of::<T>().div_ceil(align) * align
+}
+
+/// Size of the data in a custom block containing a `T`
+pub(crate) const fn custom_size<T: Custom>() -> usize {
+    if T::BORROW_CHECKED {
+        borrow_flag_offset::<T>() + core::mem::size_of::<BorrowFlag>()
+    } else {
+        core::mem::size_of::<T>()
+    }
+}
+
+/// Reset the borrow flag of a newly allocated custom block, `data` points to the block's data.
+/// This does nothing when `T::BORROW_CHECKED` isn't set
+pub(crate) unsafe fn init_borrow_flag<T: Custom>(data: *mut u8) {
+    if T::BORROW_CHECKED {
+        core::ptr::write_unaligned(
+            data.add(borrow_flag_offset::<T>()) as *mut BorrowFlag,
+            BorrowFlag::new(0),
+        )
+    }
+}
+
 unsafe impl<T: 'static + Custom> ToValue for T {
     fn to_value(self) -> Value {
-        let val: crate::Pointer<T> = Pointer::alloc_custom(self);
+        let val: crate::Pointer<T> = if self.heap_size() > 0 {
+            Pointer::alloc_custom_mem(self)
+        } else {
+            Pointer::alloc_custom(self)
+        };
         val.to_value()
     }
 }
 
+/// Custom types that can be serialized using `Marshal`, `custom_serialize!` can be used to set the
+/// `serialize` and `deserialize` operations for a type implementing `Serialize`
+///
+/// Values are deserialized by looking up the custom operations by name, so `register` needs to be
+/// called before unmarshaling a value
+///
+/// ```rust
+/// use ocaml::custom::{Deserializer, Serialize, Serializer};
+///
+/// struct Point {
+///     x: f64,
+///     y: f64,
+/// }
+///
+/// ocaml::custom_serialize!(Point);
+///
+/// impl Serialize for Point {
+///     fn serialize(&self, s: &mut Serializer) {
+///         s.write_f64(self.x);
+///         s.write_f64(self.y);
+///     }
+///
+///     fn deserialize(d: &mut Deserializer) -> Option<Self> {
+///         Some(Point {
+///             x: d.read_f64(),
+///             y: d.read_f64(),
+///         })
+///     }
+/// }
+/// ```
+pub trait Serialize: Custom + Sized {
+    /// Write the value
+    fn serialize(&self, s: &mut Serializer);
+
+    /// Read a value written by `serialize`, returning `None` causes `input_value` to fail
+    fn deserialize(d: &mut Deserializer) -> Option<Self>;
+}
+
+/// Writes data while a custom value is being serialized, integers are stored in big-endian
+/// order
+pub struct Serializer(());
+
+/// Reads data while a custom value is being deserialized
+pub struct Deserializer(());
+
+macro_rules! serializer_impl {
+    ($($write:ident, $read:ident, $t:ty, $ser:ident, $de:ident, $x:ty);* $(;)?) => {
+        impl Serializer {
+            $(
+                #[doc = concat!("Write a `", stringify!($t), "`")]
+                pub fn $write(&mut self, x: $t) {
+                    unsafe { sys::$ser(x as $x) }
+                }
+            )*
+        }
+
+        impl Deserializer {
+            $(
+                #[doc = concat!("Read a `", stringify!($t), "`")]
+                pub fn $read(&mut self) -> $t {
+                    unsafe { sys::$de() as $t }
+                }
+            )*
+        }
+    };
+}
+
+serializer_impl! {
+    write_u8, read_u8, u8, caml_serialize_int_1, caml_deserialize_uint_1, i32;
+    write_i8, read_i8, i8, caml_serialize_int_1, caml_deserialize_sint_1, i32;
+    write_u16, read_u16, u16, caml_serialize_int_2, caml_deserialize_uint_2, i32;
+    write_i16, read_i16, i16, caml_serialize_int_2, caml_deserialize_sint_2, i32;
+    write_u32, read_u32, u32, caml_serialize_int_4, caml_deserialize_uint_4, i32;
+    write_i32, read_i32, i32, caml_serialize_int_4, caml_deserialize_sint_4, i32;
+    write_u64, read_u64, u64, caml_serialize_int_8, caml_deserialize_uint_8, i64;
+    write_i64, read_i64, i64, caml_serialize_int_8, caml_deserialize_sint_8, i64;
+    write_f32, read_f32, f32, caml_serialize_float_4, caml_deserialize_float_4, f32;
+    write_f64, read_f64, f64, caml_serialize_float_8, caml_deserialize_float_8, f64;
+}
+
+impl Serializer {
+    /// Write bytes, the length isn't included so it should be written first when it isn't known
+    /// ahead of time
+    pub fn write_bytes(&mut self, x: &[u8]) {
+        unsafe { sys::caml_serialize_block_1(x.as_ptr() as *const _, x.len() as sys::Intnat) }
+    }
+}
+
+impl Deserializer {
+    /// Fill `dest` with bytes written by `Serializer::write_bytes`
+    pub fn read_bytes(&mut self, dest: &mut [u8]) {
+        unsafe {
+            sys::caml_deserialize_block_1(dest.as_mut_ptr() as *mut _, dest.len() as sys::Intnat)
+        }
+    }
+}
+
+/// `serialize` custom operation for types implementing `Serialize`
+///
+/// The size of the deserialized value is the same on 32 and 64-bit platforms, so values should
+/// only be read on platforms with the same pointer size
+///
+/// # Safety
+/// This should only be called by the OCaml runtime, as the `serialize` operation for `T`
+pub unsafe extern "C" fn serialize<T: Serialize>(
+    v: Value,
+    bsize_32: *mut Uint,
+    bsize_64: *mut Uint,
+) {
+    let p: Pointer<T> = Pointer::from_value_unchecked(v);
+    p.as_ref().serialize(&mut Serializer(()));
+    *bsize_32 = custom_size::<T>() as Uint;
+    *bsize_64 = custom_size::<T>() as Uint;
+}
+
+/// `deserialize` custom operation for types implementing `Serialize`
+///
+/// # Safety
+/// This should only be called by the OCaml runtime, as the `deserialize` operation for `T`
+pub unsafe extern "C" fn deserialize<T: Serialize>(dst: *mut core::ffi::c_void) -> Uint {
+    match T::deserialize(&mut Deserializer(())) {
+        Some(x) => {
+            core::ptr::write_unaligned(dst as *mut T, x);
+            init_borrow_flag::<T>(dst as *mut u8);
+            custom_size::<T>() as Uint
+        }
+        None => sys::caml_deserialize_error(
+            "input_value: invalid data for custom block\0".as_ptr() as *const sys::Char,
+        ),
+    }
+}
+
+/// Register the custom operations for `T` with the OCaml runtime, this is required to unmarshal
+/// values with a `deserialize` operation and should be called once, before any values are read
+pub fn register<T: Custom>() {
+    unsafe {
+        sys::caml_register_custom_operations(T::ops() as *const _ as *mut sys::custom_operations)
+    }
+}
+
+/// `finalize` custom operation that drops the Rust value
+///
+/// # Safety
+/// This should only be called by the OCaml runtime, as the `finalize` operation for `T`
+pub unsafe extern "C" fn finalize<T: Custom>(v: Value) {
+    let p: Pointer<T> = Pointer::from_value_unchecked(v);
+    p.drop_in_place()
+}
+
+fn ordering(x: core::cmp::Ordering) -> i32 {
+    match x {
+        core::cmp::Ordering::Less => -1,
+        core::cmp::Ordering::Equal => 0,
+        core::cmp::Ordering::Greater => 1,
+    }
+}
+
+/// `compare` custom operation using `Ord`
+///
+/// # Safety
+/// This should only be called by the OCaml runtime, as the `compare` operation for `T`
+pub unsafe extern "C" fn compare<T: Custom + Ord>(a: Value, b: Value) -> i32 {
+    let a: Pointer<T> = Pointer::from_value_unchecked(a);
+    let b: Pointer<T> = Pointer::from_value_unchecked(b);
+    ordering(a.as_ref().cmp(b.as_ref()))
+}
+
+/// `compare_ext` custom operation using `PartialOrd<Int>`, this is used to compare custom values
+/// with integers. Values that can't be compared are equal
+///
+/// # Safety
+/// This should only be called by the OCaml runtime, as the `compare_ext` operation for `T`
+pub unsafe extern "C" fn compare_ext<T: Custom + PartialOrd<Int>>(a: Value, b: Value) -> i32 {
+    // Either argument can be the integer
+    let (p, i, sign) = if a.is_long() { (b, a, -1) } else { (a, b, 1) };
+    let p: Pointer<T> = Pointer::from_value_unchecked(p);
+    match p.as_ref().partial_cmp(&i.int_val()) {
+        Some(x) => ordering(x) * sign,
+        None => 0,
+    }
+}
+
+/// FNV-1a, this is used instead of `DefaultHasher` since it's also available without `std` and
+/// the result doesn't depend on the Rust version
+struct Fnv(u64);
+
+impl core::hash::Hasher for Fnv {
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= *b as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// `hash` custom operation using `Hash`
+///
+/// # Safety
+/// This should only be called by the OCaml runtime, as the `hash` operation for `T`
+pub unsafe extern "C" fn hash<T: Custom + core::hash::Hash>(v: Value) -> Int {
+    use core::hash::Hasher;
+    let p: Pointer<T> = Pointer::from_value_unchecked(v);
+    let mut h = Fnv(0xcbf2_9ce4_8422_2325);
+    p.as_ref().hash(&mut h);
+    h.finish() as Int
+}
+
 /// Create a custom OCaml type from an existing Rust type
 ///
 /// See [the struct
@@ -176,6 +445,14 @@ macro_rules! custom {
             }
         }
     };
+    {name : $name:expr, serialize $(, $($k:ident : $v:expr),*)? $(,)? } => {
+        $crate::custom! {
+            name: $name,
+            serialize: $crate::custom::serialize::<Self>,
+            deserialize: $crate::custom::deserialize::<Self>
+            $(, $($k: $v),*)?
+        }
+    };
     {name : $name:expr $(, fixed_length: $fl:expr)? $(, $($k:ident : $v:expr),*)? $(,)? } => {
         const NAME: &'static str = concat!($name, "\0");
 
@@ -220,6 +497,52 @@ macro_rules! custom_finalize {
     };
 }
 
+/// Derives `Custom` for a type implementing `custom::Serialize`, using its `serialize` and
+/// `deserialize` methods for the custom operations of the same name
+///
+/// Serialized values are matched to their custom operations by identifier, so the identifier
+/// includes the module path of the type: types with the same name in different modules or crates
+/// don't collide
+///
+/// ```rust,no_run
+/// # struct MyType(i64);
+/// # unsafe extern "C" fn mytype_finalizer(_: ocaml::Value) {}
+/// # impl ocaml::custom::Serialize for MyType {
+/// #     fn serialize(&self, s: &mut ocaml::custom::Serializer) { s.write_i64(self.0) }
+/// #     fn deserialize(d: &mut ocaml::custom::Deserializer) -> Option<Self> { Some(MyType(d.read_i64())) }
+/// # }
+/// ocaml::custom_serialize!(MyType {
+///     finalize: mytype_finalizer
+/// });
+///
+/// // Which is a shortcut for:
+///
+/// # struct MyType2(i64);
+/// # impl ocaml::custom::Serialize for MyType2 {
+/// #     fn serialize(&self, s: &mut ocaml::custom::Serializer) { s.write_i64(self.0) }
+/// #     fn deserialize(d: &mut ocaml::custom::Deserializer) -> Option<Self> { Some(MyType2(d.read_i64())) }
+/// # }
+/// impl ocaml::Custom for MyType2 {
+///     ocaml::custom! {
+///         name: concat!(module_path!(), "::MyType2"),
+///         serialize,
+///         finalize: mytype_finalizer
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! custom_serialize {
+    ($name:ident $({$($k:ident : $v:expr),* $(,)? })?) => {
+        impl $crate::Custom for $name {
+            $crate::custom! {
+                name: concat!(module_path!(), "::", stringify!($name)),
+                serialize
+                $(, $($k: $v),*)?
+            }
+        }
+    };
+}
+
 /// Default CustomOps
 pub const DEFAULT_CUSTOM_OPS: CustomOps = CustomOps {
     identifier: core::ptr::null(),
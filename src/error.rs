@@ -1,4 +1,6 @@
-use crate::{FromValue, ToValue, Value};
+use crate::{FromValue, Root, Tag, ToValue, TryFromValue, Value};
+
+use alloc::borrow::Cow;
 
 /// Errors that are translated directly into OCaml exceptions
 #[derive(Debug)]
@@ -7,10 +9,10 @@ pub enum CamlError {
     NotFound,
 
     /// Failure
-    Failure(&'static str),
+    Failure(Cow<'static, str>),
 
     /// Invalid_argument
-    InvalidArgument(&'static str),
+    InvalidArgument(Cow<'static, str>),
 
     /// Out_of_memory
     OutOfMemory,
@@ -19,7 +21,7 @@ pub enum CamlError {
     StackOverflow,
 
     /// Sys_error
-    SysError(&'static str),
+    SysError(Cow<'static, str>),
 
     /// End_of_file
     EndOfFile,
@@ -33,13 +35,424 @@ pub enum CamlError {
     /// Sys_blocked_io
     SysBlockedIo,
 
-    /// A pre-allocated OCaml exception
-    Exception(Value),
+    /// A pre-allocated OCaml exception and the backtrace captured when it was raised, if
+    /// backtraces are being recorded
+    Exception(Value, Option<Backtrace>),
 
     /// An exception type and argument
     WithArg(Value, Value),
 }
 
+/// OCaml backtrace captured when an exception is raised, this is only available when backtrace
+/// recording has been enabled using `Printexc.record_backtrace true` or `OCAMLRUNPARAM=b`
+#[derive(Clone)]
+pub struct Backtrace(Root);
+
+impl Backtrace {
+    /// Capture the backtrace of the most recently raised exception, this should be called
+    /// immediately after an exception is returned from a callback
+    pub fn capture() -> Option<Backtrace> {
+        let bt = Value(unsafe { crate::sys::caml_get_exception_raw_backtrace(crate::sys::UNIT) });
+        if bt.size() == 0 {
+            return None;
+        }
+        Some(Backtrace(Root::from_value(bt)))
+    }
+
+    /// Get the OCaml `Printexc.raw_backtrace` value
+    pub fn value(&self) -> Value {
+        self.0.value()
+    }
+
+    /// Convert the backtrace to a string using `Printexc.raw_backtrace_to_string`, which must be
+    /// registered using
+    /// `Callback.register "Printexc.raw_backtrace_to_string" Printexc.raw_backtrace_to_string`
+    pub fn format(&self) -> Option<alloc::string::String> {
+        let f: Value = Value::named("Printexc.raw_backtrace_to_string")?;
+        let s = f.call(self.value()).ok()?;
+        let s = crate::conv::check_str(s).ok()?;
+        Some(alloc::string::String::from_utf8_lossy(s).into_owned())
+    }
+}
+
+impl core::fmt::Debug for Backtrace {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "Backtrace({} frames)", self.value().size())
+    }
+}
+
+/// Returns the constructor of an exception value, the constructor is a block with `Tag::OBJECT`
+/// containing the exception name and a unique id. Exceptions without arguments are represented
+/// by the constructor itself, otherwise the constructor is stored in the first field of a block
+/// followed by the arguments.
+fn exception_constructor(exn: Value) -> Option<Value> {
+    if !exn.is_block() {
+        return None;
+    }
+
+    let tag = exn.tag();
+    if tag == Tag::OBJECT {
+        return Some(exn);
+    }
+
+    if tag == Tag(0) && exn.size() > 0 {
+        let c = exn.field::<Value>(0);
+        if c.is_block() && c.tag() == Tag::OBJECT {
+            return Some(c);
+        }
+    }
+
+    None
+}
+
+fn exception_arg_string(exn: Value) -> Option<Cow<'static, str>> {
+    let args = CamlError::exception_args(exn);
+    if args.len() != 1 {
+        return None;
+    }
+
+    let s = crate::conv::check_str(args[0]).ok()?;
+    Some(Cow::Owned(
+        alloc::string::String::from_utf8_lossy(s).into_owned(),
+    ))
+}
+
+fn decode_builtin(exn: Value) -> Option<CamlError> {
+    match CamlError::exception_name(exn).as_deref() {
+        Some("Not_found") => Some(CamlError::NotFound),
+        Some("End_of_file") => Some(CamlError::EndOfFile),
+        Some("Division_by_zero") => Some(CamlError::ZeroDivide),
+        Some("Out_of_memory") => Some(CamlError::OutOfMemory),
+        Some("Stack_overflow") => Some(CamlError::StackOverflow),
+        Some("Sys_blocked_io") => Some(CamlError::SysBlockedIo),
+        Some("Failure") => exception_arg_string(exn).map(CamlError::Failure),
+        Some("Invalid_argument") => exception_arg_string(exn).map(CamlError::InvalidArgument),
+        Some("Sys_error") => exception_arg_string(exn).map(CamlError::SysError),
+        _ => None,
+    }
+}
+
+fn same_builtin(a: &CamlError, b: &CamlError) -> bool {
+    match (a, b) {
+        (CamlError::Failure(a), CamlError::Failure(b))
+        | (CamlError::InvalidArgument(a), CamlError::InvalidArgument(b))
+        | (CamlError::SysError(a), CamlError::SysError(b)) => a == b,
+        (CamlError::NotFound, CamlError::NotFound)
+        | (CamlError::EndOfFile, CamlError::EndOfFile)
+        | (CamlError::ZeroDivide, CamlError::ZeroDivide)
+        | (CamlError::OutOfMemory, CamlError::OutOfMemory)
+        | (CamlError::StackOverflow, CamlError::StackOverflow)
+        | (CamlError::SysBlockedIo, CamlError::SysBlockedIo) => true,
+        _ => false,
+    }
+}
+
+// Decoded built-in exceptions don't carry a backtrace, so matching on them doesn't depend on
+// whether backtraces are recorded. The most recently decoded one is kept here along with the
+// original exception and its backtrace, which are restored when the same exception is re-raised
+#[cfg(not(feature = "no-std"))]
+std::thread_local! {
+    static LAST_BUILTIN: core::cell::RefCell<Option<(CamlError, Root, Backtrace)>> =
+        const { core::cell::RefCell::new(None) };
+}
+
+#[cfg(not(feature = "no-std"))]
+fn set_last_builtin(exn: Value, bt: Option<Backtrace>) {
+    let last = match (decode_builtin(exn), bt) {
+        (Some(e), Some(bt)) => Some((e, Root::from_value(exn), bt)),
+        _ => None,
+    };
+    LAST_BUILTIN.with(|x| *x.borrow_mut() = last);
+}
+
+#[cfg(feature = "no-std")]
+fn set_last_builtin(_exn: Value, _bt: Option<Backtrace>) {}
+
+/// Returns the original exception and backtrace if `e` is the most recently decoded built-in
+/// exception, `take` clears it
+#[cfg(not(feature = "no-std"))]
+fn last_builtin(e: &CamlError, take: bool) -> Option<(Root, Backtrace)> {
+    LAST_BUILTIN.with(|x| {
+        let mut x = x.borrow_mut();
+        match &*x {
+            Some((last, exn, bt)) if same_builtin(last, e) => {
+                let res = Some((exn.clone(), bt.clone()));
+                if take {
+                    *x = None;
+                }
+                res
+            }
+            _ => None,
+        }
+    })
+}
+
+#[cfg(feature = "no-std")]
+fn last_builtin(_e: &CamlError, _take: bool) -> Option<(Root, Backtrace)> {
+    None
+}
+
+impl CamlError {
+    /// Convert an OCaml exception value to a `CamlError`, built-in exceptions are decoded into the
+    /// matching variant and anything else is returned as `CamlError::Exception` along with the
+    /// current backtrace. The backtrace of a built-in exception is kept separately, it is returned
+    /// by `CamlError::backtrace` and restored when the same exception is re-raised
+    pub fn from_exception(exn: Value) -> CamlError {
+        crate::frame!((x) {
+            x = exn;
+            let bt = Backtrace::capture();
+            match decode_builtin(x) {
+                Some(e) => {
+                    set_last_builtin(x, bt);
+                    e
+                }
+                None => CamlError::Exception(x, bt),
+            }
+        })
+    }
+
+    /// Returns the name of an exception value. Built-in exceptions use their unqualified name
+    /// (`Not_found`), other exceptions include the module path (`Dune__exe__Main.My_error`). The
+    /// name is copied since the string is owned by the OCaml heap
+    pub fn exception_name(exn: Value) -> Option<alloc::string::String> {
+        let c = exception_constructor(exn)?;
+        let name = c.field::<Value>(0);
+        let s = crate::conv::check_str(name).ok()?;
+        core::str::from_utf8(s)
+            .ok()
+            .map(alloc::string::String::from)
+    }
+
+    /// Returns the arguments of an exception value, this is empty for exceptions without arguments
+    /// or values that are not exceptions
+    pub fn exception_args(exn: Value) -> alloc::vec::Vec<Value> {
+        match exception_constructor(exn) {
+            Some(c) if c != exn => (1..exn.size()).map(|i| exn.field::<Value>(i)).collect(),
+            _ => alloc::vec::Vec::new(),
+        }
+    }
+
+    /// Returns the backtrace captured when the exception was raised, for built-in exceptions
+    /// this is only available for the most recently decoded one
+    pub fn backtrace(&self) -> Option<Backtrace> {
+        match self {
+            CamlError::Exception(_, bt) => bt.clone(),
+            e => last_builtin(e, false).map(|(_, bt)| bt),
+        }
+    }
+
+    /// Returns the name of the exception
+    pub fn name(&self) -> Option<Cow<'static, str>> {
+        let name = match self {
+            CamlError::NotFound => "Not_found",
+            CamlError::Failure(_) => "Failure",
+            CamlError::InvalidArgument(_) => "Invalid_argument",
+            CamlError::OutOfMemory => "Out_of_memory",
+            CamlError::StackOverflow => "Stack_overflow",
+            CamlError::SysError(_) => "Sys_error",
+            CamlError::EndOfFile => "End_of_file",
+            CamlError::ZeroDivide => "Division_by_zero",
+            CamlError::ArrayBoundError => "Invalid_argument",
+            CamlError::SysBlockedIo => "Sys_blocked_io",
+            CamlError::Exception(exn, _) | CamlError::WithArg(exn, _) => {
+                return CamlError::exception_name(*exn).map(Cow::Owned)
+            }
+        };
+        Some(Cow::Borrowed(name))
+    }
+}
+
+/// Formats an exception argument the same way as `Printexc.to_string`: strings are quoted,
+/// integers are printed and everything else is replaced with `_`
+fn fmt_exception_arg(f: &mut core::fmt::Formatter, arg: Value) -> core::fmt::Result {
+    if !arg.is_block() {
+        return write!(f, "{}", arg.int_val());
+    }
+
+    match crate::conv::check_str(arg) {
+        Ok(s) => write!(f, "{:?}", alloc::string::String::from_utf8_lossy(s)),
+        Err(_) => write!(f, "_"),
+    }
+}
+
+fn fmt_exception(f: &mut core::fmt::Formatter, name: &str, args: &[Value]) -> core::fmt::Result {
+    write!(f, "{}", name)?;
+    if args.is_empty() {
+        return Ok(());
+    }
+
+    write!(f, "(")?;
+    for (i, arg) in args.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        fmt_exception_arg(f, *arg)?;
+    }
+    write!(f, ")")
+}
+
+/// Converts an exception to a string using `Printexc.to_string`, which must be registered using
+/// `Callback.register "Printexc.to_string" Printexc.to_string`
+fn printexc_to_string(exn: Value) -> Option<alloc::string::String> {
+    let to_string: Value = Value::named("Printexc.to_string")?;
+    let s = to_string.call(exn).ok()?;
+    let s = crate::conv::check_str(s).ok()?;
+    Some(alloc::string::String::from_utf8_lossy(s).into_owned())
+}
+
+impl core::fmt::Display for CamlError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            CamlError::Failure(s) | CamlError::InvalidArgument(s) | CamlError::SysError(s) => {
+                write!(f, "{}({:?})", self.name().unwrap_or_default(), s)
+            }
+            CamlError::ArrayBoundError => write!(f, "Invalid_argument(\"index out of bounds\")"),
+            CamlError::Exception(exn, _) => {
+                if let Some(s) = printexc_to_string(*exn) {
+                    return write!(f, "{}", s);
+                }
+                let name = CamlError::exception_name(*exn);
+                let name = name.as_deref().unwrap_or("exception");
+                fmt_exception(f, name, &CamlError::exception_args(*exn))
+            }
+            CamlError::WithArg(exn, arg) => {
+                let name = CamlError::exception_name(*exn);
+                let name = name.as_deref().unwrap_or("exception");
+                fmt_exception(f, name, &[*arg])
+            }
+            _ => write!(f, "{}", self.name().unwrap_or_default()),
+        }
+    }
+}
+
+/// Errors returned by `TryFromValue` when an OCaml value does not have the expected layout
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConversionError {
+    /// Expected an immediate value (`int`, `bool`, constant constructor, ...), found a block
+    ExpectedImmediate,
+
+    /// Expected a block, found an immediate value
+    ExpectedBlock,
+
+    /// Block has an unexpected tag
+    InvalidTag {
+        /// Expected tag
+        expected: Tag,
+        /// Tag of the value being converted
+        found: Tag,
+    },
+
+    /// Block has an unexpected number of fields
+    InvalidSize {
+        /// Expected number of fields
+        expected: usize,
+        /// Number of fields in the value being converted
+        found: usize,
+    },
+
+    /// Custom block has an unexpected identifier
+    InvalidCustom {
+        /// Expected custom operations identifier
+        expected: &'static str,
+        /// Identifier of the value being converted
+        found: &'static str,
+    },
+
+    /// Custom block wasn't created using the custom operations for the expected Rust type
+    InvalidPointer {
+        /// Expected Rust type
+        expected: &'static str,
+        /// Custom operations identifier of the expected type
+        identifier: &'static str,
+        /// Identifier of the value being converted
+        found: &'static str,
+    },
+
+    /// Bigarray has an unexpected number of dimensions
+    InvalidRank {
+        /// Expected number of dimensions
+        expected: usize,
+        /// Number of dimensions of the value being converted
+        found: usize,
+    },
+
+    /// Bigarray has an unexpected element kind
+    InvalidKind {
+        /// Expected element kind
+        expected: &'static str,
+        /// Element kind of the value being converted
+        found: &'static str,
+    },
+
+    /// Bigarray has an unexpected layout
+    InvalidLayout {
+        /// Expected layout
+        expected: &'static str,
+        /// Layout of the value being converted
+        found: &'static str,
+    },
+
+    /// String is not valid UTF-8
+    InvalidUtf8,
+
+    /// Value does not match any of the constructors of the target type
+    UnknownVariant,
+}
+
+impl core::fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            ConversionError::ExpectedImmediate => {
+                write!(f, "expected an immediate value, found a block")
+            }
+            ConversionError::ExpectedBlock => {
+                write!(f, "expected a block, found an immediate value")
+            }
+            ConversionError::InvalidTag { expected, found } => {
+                write!(
+                    f,
+                    "expected a block with tag {}, found tag {}",
+                    expected.0, found.0
+                )
+            }
+            ConversionError::InvalidSize { expected, found } => {
+                write!(
+                    f,
+                    "expected a block of size {}, found size {}",
+                    expected, found
+                )
+            }
+            ConversionError::InvalidCustom { expected, found } => {
+                write!(f, "expected custom block {}, found {}", expected, found)
+            }
+            ConversionError::InvalidPointer {
+                expected,
+                identifier,
+                found,
+            } => write!(
+                f,
+                "expected custom block for {} ({}), found {}",
+                expected, identifier, found
+            ),
+            ConversionError::InvalidRank { expected, found } => write!(
+                f,
+                "expected a bigarray with {} dimension(s), found {}",
+                expected, found
+            ),
+            ConversionError::InvalidKind { expected, found } => write!(
+                f,
+                "expected a bigarray of kind {}, found {}",
+                expected, found
+            ),
+            ConversionError::InvalidLayout { expected, found } => {
+                write!(f, "expected a bigarray with {}, found {}", expected, found)
+            }
+            ConversionError::InvalidUtf8 => write!(f, "invalid UTF-8"),
+            ConversionError::UnknownVariant => write!(f, "unknown variant"),
+        }
+    }
+}
+
 /// Error returned by `ocaml-rs` functions
 #[derive(Debug)]
 pub enum Error {
@@ -49,13 +462,19 @@ pub enum Error {
     /// Array is not a double array
     NotDoubleArray,
 
+    /// A Rust closure created using `Fn1::new` was called again before it returned
+    ReentrantClosure,
+
     /// Error message
-    Message(&'static str),
+    Message(Cow<'static, str>),
 
     /// General error
     #[cfg(not(feature = "no-std"))]
     Error(Box<dyn std::error::Error>),
 
+    /// Value has an unexpected layout, returned by `TryFromValue`
+    Conversion(ConversionError),
+
     /// OCaml exceptions
     Caml(CamlError),
 }
@@ -73,10 +492,16 @@ impl From<CamlError> for Error {
     }
 }
 
+impl From<ConversionError> for Error {
+    fn from(x: ConversionError) -> Error {
+        Error::Conversion(x)
+    }
+}
+
 impl Error {
     /// Re-raise an existing exception value
     pub fn reraise(exc: Value) -> Result<(), Error> {
-        Err(CamlError::Exception(exc).into())
+        Err(CamlError::Exception(exc, None).into())
     }
 
     /// Raise an exception that has been registered using `Callback.register_exception` with no
@@ -85,12 +510,12 @@ impl Error {
         let value = match Value::named(exc.as_ref()) {
             Some(v) => v,
             None => {
-                return Err(Error::Message(
+                return Err(Error::Message(Cow::Borrowed(
                     "Value has not been registered with the OCaml runtime",
-                ))
+                )))
             }
         };
-        Err(CamlError::Exception(value).into())
+        Err(CamlError::Exception(value, None).into())
     }
 
     /// Raise an exception that has been registered using `Callback.register_exception` with an
@@ -99,15 +524,24 @@ impl Error {
         let value = match Value::named(exc.as_ref()) {
             Some(v) => v,
             None => {
-                return Err(Error::Message(
+                return Err(Error::Message(Cow::Borrowed(
                     "Value has not been registered with the OCaml runtime",
-                ))
+                )))
             }
         };
 
         Err(CamlError::WithArg(value, arg.to_value()).into())
     }
 
+    /// Error returned when an exception has not been registered using
+    /// `Callback.register_exception`, used by `derive(Exception)`
+    #[doc(hidden)]
+    pub fn not_registered(name: &str) -> Error {
+        Error::Message(
+            alloc::format!("{} has not been registered with the OCaml runtime", name).into(),
+        )
+    }
+
     /// Raise `Not_found`
     pub fn not_found() -> Result<(), Error> {
         Err(CamlError::NotFound.into())
@@ -118,14 +552,20 @@ impl Error {
         Err(CamlError::OutOfMemory.into())
     }
 
-    /// Raise `Failure`
-    pub fn failwith(s: &'static str) -> Result<(), Error> {
-        Err(CamlError::Failure(s).into())
+    /// Raise `Failure`, the message can be a `&'static str` or a `String`:
+    /// `Error::failwith(format!("invalid offset: {}", offset))`
+    pub fn failwith<S: Into<Cow<'static, str>>>(s: S) -> Result<(), Error> {
+        Err(CamlError::Failure(s.into()).into())
     }
 
     /// Raise `Invalid_argument`
-    pub fn invalid_argument(s: &'static str) -> Result<(), Error> {
-        Err(CamlError::Failure(s).into())
+    pub fn invalid_argument<S: Into<Cow<'static, str>>>(s: S) -> Result<(), Error> {
+        Err(CamlError::InvalidArgument(s.into()).into())
+    }
+
+    /// Raise `Sys_error`
+    pub fn sys_error<S: Into<Cow<'static, str>>>(s: S) -> Result<(), Error> {
+        Err(CamlError::SysError(s.into()).into())
     }
 
     #[doc(hidden)]
@@ -148,12 +588,71 @@ impl Error {
         loop {}
     }
 
+    /// Raise `Invalid_argument` for a function argument that couldn't be converted, this is used
+    /// by `ocaml::func` when `TryFromValue` fails. Errors other than `Error::Conversion` are raised
+    /// the same way they would be when returned from the function body.
+    #[doc(hidden)]
+    pub fn raise_argument_error(name: &str, err: Error) -> ! {
+        match err {
+            Error::Conversion(e) => {
+                let s = crate::util::format_value(format_args!("{}: {}", name, e));
+                unsafe {
+                    crate::sys::caml_invalid_argument_value(s.0);
+                }
+            }
+            err => {
+                let _ = Err::<(), Error>(err).to_value();
+            }
+        }
+        #[allow(clippy::empty_loop)]
+        loop {}
+    }
+
     /// Get named error registered using `Callback.register_exception`
     pub fn named<S: AsRef<str>>(s: S) -> Option<Value> {
         Value::named(s.as_ref())
     }
 }
 
+/// Convert a Rust error into an OCaml exception, implemented using `derive(Exception)`
+///
+/// Functions defined using `ocaml::func` that return `Result<T, E>` raise `E` using
+/// `into_exception` when `E` implements `IntoException`, even if it also implements
+/// `std::error::Error`
+pub trait IntoException {
+    /// Convert to an `Error` that raises the matching OCaml exception
+    fn into_exception(self) -> Error;
+}
+
+/// Return value of a function defined using `ocaml::func`, `FuncReturnException` is used for
+/// `Result<T, E: IntoException>` and `FuncReturnValue` for any other `ToValue` type
+#[doc(hidden)]
+pub struct FuncReturn<T>(pub Option<T>);
+
+#[doc(hidden)]
+pub trait FuncReturnException {
+    fn func_return(&mut self) -> Value;
+}
+
+impl<T: ToValue, E: IntoException> FuncReturnException for FuncReturn<Result<T, E>> {
+    fn func_return(&mut self) -> Value {
+        let x = self.0.take().expect("return value already converted");
+        x.map_err(IntoException::into_exception).to_value()
+    }
+}
+
+#[doc(hidden)]
+pub trait FuncReturnValue {
+    fn func_return(&mut self) -> Value;
+}
+
+impl<T: ToValue> FuncReturnValue for &mut FuncReturn<T> {
+    fn func_return(&mut self) -> Value {
+        let x = self.0.take().expect("return value already converted");
+        x.to_value()
+    }
+}
+
 #[cfg(not(feature = "no-std"))]
 unsafe impl<T: ToValue, E: 'static + std::error::Error> ToValue for Result<T, E> {
     fn to_value(self) -> Value {
@@ -167,13 +666,61 @@ unsafe impl<T: ToValue, E: 'static + std::error::Error> ToValue for Result<T, E>
     }
 }
 
+/// Convert an error message to an OCaml string, dropping the Rust string before an exception is
+/// raised
+fn message_value(s: Cow<'static, str>) -> Value {
+    let v = crate::util::format_value(format_args!("{}", s));
+    drop(s);
+    v
+}
+
+/// Formats an error followed by each of its sources: `error: source: source`
+#[cfg(not(feature = "no-std"))]
+struct ErrorChain<'a>(&'a dyn std::error::Error);
+
+#[cfg(not(feature = "no-std"))]
+impl<'a> core::fmt::Display for ErrorChain<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{}", self.0)?;
+        let mut source = self.0.source();
+        while let Some(e) = source {
+            write!(f, ": {}", e)?;
+            source = e.source();
+        }
+        Ok(())
+    }
+}
+
 unsafe impl<T: ToValue> ToValue for Result<T, Error> {
     fn to_value(self) -> Value {
+        // A built-in exception decoded from OCaml is raised again using the original exception, so
+        // the backtrace can be restored
+        let builtin = match &self {
+            Err(Error::Caml(e)) => last_builtin(e, true),
+            _ => None,
+        };
+        if let Some((exn, bt)) = builtin {
+            let (e, b) = (exn.value(), bt.value());
+            drop((exn, bt, self));
+            unsafe {
+                crate::sys::caml_restore_raw_backtrace(e.0, b.0);
+                crate::sys::caml_raise(e.0)
+            };
+            return Value::unit();
+        }
+
         match self {
             Ok(x) => return x.to_value(),
-            Err(Error::Caml(CamlError::Exception(e))) => unsafe {
-                crate::sys::caml_raise(e.0);
-            },
+            Err(Error::Caml(CamlError::Exception(e, bt))) => {
+                // Restore the original backtrace, this is the same as `Printexc.raise_with_backtrace`,
+                // the root is removed before raising since destructors don't run
+                if let Some(bt) = bt {
+                    let b = bt.value();
+                    drop(bt);
+                    unsafe { crate::sys::caml_restore_raw_backtrace(e.0, b.0) };
+                }
+                unsafe { crate::sys::caml_raise(e.0) };
+            }
             Err(Error::Caml(CamlError::NotFound)) => unsafe {
                 crate::sys::caml_raise_not_found();
             },
@@ -196,41 +743,38 @@ unsafe impl<T: ToValue> ToValue for Result<T, Error> {
                 crate::sys::caml_raise_sys_blocked_io()
             },
             Err(Error::Caml(CamlError::InvalidArgument(s))) => {
-                unsafe {
-                    let s = crate::util::CString::new(s).expect("Invalid C string");
-                    crate::sys::caml_invalid_argument(s.as_ptr() as *const ocaml_sys::Char)
-                };
+                let s = message_value(s);
+                unsafe { crate::sys::caml_invalid_argument_value(s.0) };
             }
             Err(Error::Caml(CamlError::WithArg(a, b))) => unsafe {
                 crate::sys::caml_raise_with_arg(a.0, b.0)
             },
             Err(Error::Caml(CamlError::SysError(s))) => {
-                unsafe {
-                    let s = s.to_value();
-                    crate::sys::caml_raise_sys_error(s.0)
-                };
+                let s = message_value(s);
+                unsafe { crate::sys::caml_raise_sys_error(s.0) };
             }
-            Err(Error::Message(s)) => {
-                unsafe {
-                    let s = crate::util::CString::new(s).expect("Invalid C string");
-                    crate::sys::caml_failwith(s.as_ptr() as *const ocaml_sys::Char)
-                };
-            }
-            Err(Error::Caml(CamlError::Failure(s))) => {
-                unsafe {
-                    let s = crate::util::CString::new(s).expect("Invalid C string");
-                    crate::sys::caml_failwith(s.as_ptr() as *const ocaml_sys::Char)
-                };
+            Err(Error::Message(s)) | Err(Error::Caml(CamlError::Failure(s))) => {
+                let s = message_value(s);
+                unsafe { crate::sys::caml_failwith_value(s.0) };
             }
             #[cfg(not(feature = "no-std"))]
             Err(Error::Error(e)) => {
-                let s = format!("{:?}\0", e);
-                unsafe { crate::sys::caml_failwith(s.as_ptr() as *const ocaml_sys::Char) };
+                let s = crate::util::format_value(format_args!("{}", ErrorChain(e.as_ref())));
+                drop(e);
+                unsafe { crate::sys::caml_failwith_value(s.0) };
+            }
+            Err(Error::Conversion(e)) => {
+                let s = crate::util::format_value(format_args!("{}", e));
+                unsafe { crate::sys::caml_invalid_argument_value(s.0) };
             }
             Err(Error::NotDoubleArray) => {
                 let s = "invalid double array\0";
                 unsafe { crate::sys::caml_failwith(s.as_ptr() as *const ocaml_sys::Char) };
             }
+            Err(Error::ReentrantClosure) => {
+                let s = "closure called again before returning\0";
+                unsafe { crate::sys::caml_failwith(s.as_ptr() as *const ocaml_sys::Char) };
+            }
             Err(Error::NotCallable) => {
                 let s = "value is not callable\0";
                 unsafe { crate::sys::caml_failwith(s.as_ptr() as *const ocaml_sys::Char) };
@@ -244,9 +788,21 @@ unsafe impl<T: ToValue> ToValue for Result<T, Error> {
 unsafe impl<T: FromValue> FromValue for Result<T, crate::Error> {
     fn from_value(value: Value) -> Result<T, crate::Error> {
         if value.is_exception_result() {
-            return Err(CamlError::Exception(value.exception().unwrap()).into());
+            return Err(CamlError::Exception(value.exception().unwrap(), None).into());
         }
 
         Ok(T::from_value(value))
     }
 }
+
+unsafe impl<T: TryFromValue> TryFromValue for Result<T, crate::Error> {
+    fn try_from_value(value: Value) -> Result<Result<T, crate::Error>, crate::Error> {
+        if value.is_exception_result() {
+            return Ok(Err(
+                CamlError::Exception(value.exception().unwrap(), None).into()
+            ));
+        }
+
+        T::try_from_value(value).map(Ok)
+    }
+}
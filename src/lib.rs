@@ -104,20 +104,24 @@
 #[cfg(all(feature = "link", feature = "no-std"))]
 std::compile_error!("Cannot use link and no-std features");
 
+extern crate alloc;
+
 /// The `sys` module contains the low-level implementation of the OCaml runtime
 pub use ocaml_sys as sys;
 
 #[cfg(feature = "derive")]
 pub use ocaml_derive::{
     ocaml_bytecode_func as bytecode_func, ocaml_func as func, ocaml_native_func as native_func,
-    FromValue, ToValue,
+    ocaml_sig as sig, Custom, Exception, FromValue, ToValue,
 };
 
 #[macro_use]
 mod macros;
 
+mod closure;
 mod conv;
 mod error;
+mod root;
 mod tag;
 mod types;
 mod util;
@@ -129,15 +133,27 @@ pub mod runtime;
 /// Custom types, used for allocating Rust values owned by the OCaml garbage collector
 pub mod custom;
 
+/// Serialize and deserialize OCaml values using the same format as the `Marshal` module
+pub mod marshal;
+
 pub use crate::custom::Custom;
-pub use crate::error::{CamlError, Error};
+pub use crate::error::{Backtrace, CamlError, ConversionError, Error, IntoException};
+#[doc(hidden)]
+pub use crate::error::{FuncReturn, FuncReturnException, FuncReturnValue};
+pub use crate::root::Root;
 pub use crate::runtime::*;
 pub use crate::tag::Tag;
-pub use crate::types::{bigarray, Array, List, Pointer};
-pub use crate::value::{FromValue, ToValue, Value};
+pub use crate::types::{
+    bigarray, Array, Fn1, Fn2, Fn3, Fn4, Fn5, List, Pointer, PointerMut, PointerRef,
+};
+#[doc(hidden)]
+pub use crate::types::{FuncArg, FuncArgPointer, FuncArgValue};
+pub use crate::value::{FromValue, ToValue, TryFromValue, Value};
 
 #[cfg(not(feature = "no-std"))]
-pub use crate::macros::init_panic_handler;
+pub use crate::macros::{
+    init_panic_handler, panic_policy, raise_panic, set_panic_policy, PanicPolicy,
+};
 
 /// OCaml `float`
 pub type Float = f64;
@@ -37,7 +37,7 @@ macro_rules! frame {
 #[cfg(feature = "no-std")]
 macro_rules! body {
     ($(($($param:expr),*))? $code:block) => {
-        $crate::sys::caml_body!($(($($param.0),*))? $code);
+        $crate::sys::caml_body!($(($($param.0),*))? $code)
     }
 }
 
@@ -45,34 +45,148 @@ macro_rules! body {
 static PANIC_HANDLER_INIT: std::sync::atomic::AtomicBool =
     std::sync::atomic::AtomicBool::new(false);
 
+#[cfg(not(feature = "no-std"))]
+std::thread_local! {
+    static PANIC_LOCATION: std::cell::RefCell<Option<(String, u32)>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+/// `PanicPolicy` determines how Rust panics caught by `body!` are converted to OCaml
+/// exceptions
+#[cfg(not(feature = "no-std"))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PanicPolicy {
+    /// Raise `Rust_exception` with the panic message if it has been registered using
+    /// `Callback.register_exception`, otherwise raise `Failure` (default)
+    Raise,
+
+    /// Always raise `Failure` with the panic message
+    Failure,
+
+    /// Raise the named exception, registered using `Callback.register_exception`. The exception
+    /// should be declared as `exception E of string * string * int`, containing the panic message,
+    /// file and line number. `Failure` is raised when the exception hasn't been registered
+    Exception(&'static str),
+
+    /// Abort the process
+    Abort,
+}
+
+#[cfg(not(feature = "no-std"))]
+static PANIC_POLICY: std::sync::RwLock<PanicPolicy> = std::sync::RwLock::new(PanicPolicy::Raise);
+
+/// Set the `PanicPolicy` used when a Rust panic is caught
+#[cfg(not(feature = "no-std"))]
+pub fn set_panic_policy(policy: PanicPolicy) {
+    match PANIC_POLICY.write() {
+        Ok(mut p) => *p = policy,
+        Err(e) => *e.into_inner() = policy,
+    }
+}
+
+/// Get the current `PanicPolicy`
+#[cfg(not(feature = "no-std"))]
+pub fn panic_policy() -> PanicPolicy {
+    match PANIC_POLICY.read() {
+        Ok(p) => *p,
+        Err(e) => *e.into_inner(),
+    }
+}
+
 #[cfg(not(feature = "no-std"))]
 #[doc(hidden)]
 pub fn init_panic_handler() {
-    if PANIC_HANDLER_INIT.compare_and_swap(false, true, std::sync::atomic::Ordering::Relaxed) {
+    if PANIC_HANDLER_INIT
+        .compare_exchange(
+            false,
+            true,
+            std::sync::atomic::Ordering::Relaxed,
+            std::sync::atomic::Ordering::Relaxed,
+        )
+        .is_err()
+    {
         return;
     }
 
-    ::std::panic::set_hook(Box::new(|info| {
-        let err = info.payload();
-        let msg = if err.is::<&str>() {
-            err.downcast_ref::<&str>().unwrap()
-        } else if err.is::<String>() {
-            err.downcast_ref::<String>().unwrap().as_ref()
-        } else {
-            "rust panic"
-        };
-
-        if let Some(err) = crate::Value::named("Rust_exception") {
-            crate::Error::raise_value(err, msg);
+    // The hook records where the panic happened and then calls the previous hook, which prints
+    // the message by default. Panics inside `body!` are converted to OCaml exceptions after the
+    // Rust frames have been unwound
+    let prev = ::std::panic::take_hook();
+    ::std::panic::set_hook(Box::new(move |info| {
+        let location = info
+            .location()
+            .map(|loc| (loc.file().to_string(), loc.line()));
+        PANIC_LOCATION.with(|x| *x.borrow_mut() = location);
+        prev(info)
+    }))
+}
+
+/// Convert a panic payload caught using `catch_unwind` into an OCaml exception and raise it,
+/// this should only be called once all Rust values have been dropped
+#[cfg(not(feature = "no-std"))]
+#[doc(hidden)]
+pub fn raise_panic(err: Box<dyn std::any::Any + Send>) -> ! {
+    let policy = panic_policy();
+    if policy == PanicPolicy::Abort {
+        std::process::abort()
+    }
+
+    let msg = if let Some(s) = err.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = err.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        String::from("rust panic")
+    };
+    drop(err);
+
+    let (file, line) = PANIC_LOCATION
+        .with(|x| x.borrow_mut().take())
+        .unwrap_or_default();
+
+    let name = match policy {
+        PanicPolicy::Raise => Some("Rust_exception"),
+        PanicPolicy::Exception(name) => Some(name),
+        _ => None,
+    };
+
+    match name.filter(|name| crate::Value::named::<crate::Value>(name).is_some()) {
+        Some(name) => {
+            let structured = matches!(policy, PanicPolicy::Exception(_));
+            crate::frame!((m, f) {
+                m = crate::Value::string(&msg);
+                f = crate::Value::string(&file);
+                drop(msg);
+                drop(file);
+
+                // The exception is looked up after allocating, since it may have been moved
+                let exc: crate::Value = crate::Value::named(name).unwrap();
+                unsafe {
+                    if structured {
+                        let mut args = [m.0, f.0, crate::Value::int(line as crate::Int).0];
+                        crate::sys::caml_raise_with_args(exc.0, 3, args.as_mut_ptr())
+                    } else {
+                        crate::sys::caml_raise_with_arg(exc.0, m.0)
+                    }
+                }
+            });
         }
+        None => {
+            let m = crate::Value::string(&msg);
+            drop(msg);
+            drop(file);
+            unsafe { crate::sys::caml_failwith_value(m.0) }
+        }
+    }
 
-        crate::Error::raise_failure(msg)
-    }))
+    #[allow(clippy::empty_loop)]
+    loop {}
 }
 
 /// `body!` is needed to help the OCaml runtime to manage garbage collection, it should
 /// be used to wrap the body of each function exported to OCaml. Panics from Rust code
-/// will automatically be unwound/caught here (unless the `no-std` feature is enabled)
+/// will automatically be caught here and converted to OCaml exceptions once the Rust frames
+/// have been unwound, see `PanicPolicy` (unless the `no-std` feature is enabled)
 ///
 /// ```rust
 /// #[no_mangle]
@@ -103,12 +217,15 @@ macro_rules! body {
         // Execute Rust function
         #[allow(unused_mut)]
         let mut res = || {$code };
-        let res = res();
+        let res = ::std::panic::catch_unwind(::std::panic::AssertUnwindSafe(res));
 
         #[allow(unused_unsafe)]
         unsafe { $crate::sys::set_local_roots(caml_frame) };
 
-        res
+        match res {
+            Ok(x) => x,
+            Err(e) => $crate::raise_panic(e),
+        }
     }}
 }
 
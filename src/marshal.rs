@@ -0,0 +1,122 @@
+//! Custom blocks can only be serialized if they define `serialize` in their `CustomOps`, and can
+//! only be deserialized once their custom operations have been registered using
+//! `caml_register_custom_operations`
+//!
+//! `Data` implements the same format in Rust, without calling into the OCaml runtime, it can be
+//! used to read or write marshaled data in programs that don't link the OCaml runtime
+
+use crate::{sys, Error, List, Value};
+
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+
+mod data;
+
+pub use data::{Data, FormatError};
+
+const MAGIC_SMALL: u32 = 0x8495_A6BE;
+const MAGIC_BIG: u32 = 0x8495_A6BF;
+
+/// Size of the header written by `Marshal`
+pub const HEADER_SIZE: usize = 20;
+
+/// Marshal flags, these are the same as `Marshal.extern_flags`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flag {
+    /// Don't preserve sharing
+    NoSharing,
+    /// Send function closures
+    Closures,
+    /// Ensure output is readable on 32-bit platforms
+    Compat32,
+}
+
+fn flag_list(flags: &[Flag]) -> List<crate::Int> {
+    let mut list = List::empty();
+    for flag in flags {
+        let x = match flag {
+            Flag::NoSharing => 0,
+            Flag::Closures => 1,
+            Flag::Compat32 => 2,
+        };
+        list = list.add(x);
+    }
+    list
+}
+
+fn invalid(msg: &'static str) -> Error {
+    Error::Message(Cow::Borrowed(msg))
+}
+
+fn read_u32(data: &[u8], offs: usize) -> u32 {
+    let mut x = [0u8; 4];
+    x.copy_from_slice(&data[offs..offs + 4]);
+    u32::from_be_bytes(x)
+}
+
+fn read_u64(data: &[u8], offs: usize) -> u64 {
+    let mut x = [0u8; 8];
+    x.copy_from_slice(&data[offs..offs + 8]);
+    u64::from_be_bytes(x)
+}
+
+/// Returns the total size (header and data) of the marshaled value starting at `data`, like
+/// `Marshal.total_size`. At least `HEADER_SIZE` bytes are required
+pub fn total_size(data: &[u8]) -> Result<usize, Error> {
+    if data.len() < HEADER_SIZE {
+        return Err(invalid("marshal: truncated header"));
+    }
+
+    match read_u32(data, 0) {
+        MAGIC_SMALL => Ok(HEADER_SIZE + read_u32(data, 4) as usize),
+        MAGIC_BIG => {
+            if data.len() < 32 {
+                return Err(invalid("marshal: truncated header"));
+            }
+            Ok(32 + read_u64(data, 8) as usize)
+        }
+        _ => Err(invalid("marshal: bad magic number")),
+    }
+}
+
+/// Serialize a value, this is the same as `Marshal.to_bytes`
+///
+/// # Safety
+/// Like `Marshal`, this raises `Invalid_argument` when a value can't be serialized (functional
+/// values without `Flag::Closures`, abstract values or custom blocks without `serialize`). The
+/// exception is raised from inside the runtime, skipping Rust destructors, so this should only be
+/// called from functions exported to OCaml, before any values that need to be dropped are created
+pub unsafe fn to_bytes(value: Value, flags: &[Flag]) -> Vec<u8> {
+    let mut buf: *mut sys::Char = core::ptr::null_mut();
+    let mut len: sys::Intnat = 0;
+    crate::frame!((v, f) {
+        v = value;
+        f = crate::ToValue::to_value(flag_list(flags));
+        sys::caml_output_value_to_malloc(v.0, f.0, &mut buf, &mut len);
+    });
+
+    let dest = core::slice::from_raw_parts(buf as *const u8, len as usize).to_vec();
+    sys::caml_stat_free(buf as *mut core::ffi::c_void);
+    dest
+}
+
+/// Deserialize a value, this is the same as `Marshal.from_bytes`
+///
+/// The header is checked before the data is passed to the runtime, however like `Marshal` the
+/// data itself is not validated: the result should be converted to the type that was serialized.
+///
+/// # Safety
+/// Errors in the header are returned as `Err`, however the runtime raises `Failure` when the
+/// rest of the data is invalid, skipping Rust destructors like `to_bytes`. `data` must not point
+/// to a value on the OCaml heap, since it could be moved by the garbage collector while the
+/// result is allocated
+pub unsafe fn from_bytes(data: &[u8]) -> Result<Value, Error> {
+    if total_size(data)? > data.len() {
+        return Err(invalid("marshal: truncated data"));
+    }
+
+    Ok(Value(sys::caml_input_value_from_block(
+        data.as_ptr() as *const sys::Char,
+        data.len() as sys::Intnat,
+    )))
+}
@@ -0,0 +1,660 @@
+use crate::Error;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+const MAGIC_SMALL: u32 = 0x8495_A6BE;
+const MAGIC_BIG: u32 = 0x8495_A6BF;
+
+const PREFIX_SMALL_BLOCK: u8 = 0x80;
+const PREFIX_SMALL_INT: u8 = 0x40;
+const PREFIX_SMALL_STRING: u8 = 0x20;
+const CODE_INT8: u8 = 0x00;
+const CODE_INT16: u8 = 0x01;
+const CODE_INT32: u8 = 0x02;
+const CODE_INT64: u8 = 0x03;
+const CODE_SHARED8: u8 = 0x04;
+const CODE_SHARED16: u8 = 0x05;
+const CODE_SHARED32: u8 = 0x06;
+const CODE_SHARED64: u8 = 0x14;
+const CODE_BLOCK32: u8 = 0x08;
+const CODE_BLOCK64: u8 = 0x13;
+const CODE_STRING8: u8 = 0x09;
+const CODE_STRING32: u8 = 0x0A;
+const CODE_STRING64: u8 = 0x15;
+const CODE_DOUBLE_BIG: u8 = 0x0B;
+const CODE_DOUBLE_LITTLE: u8 = 0x0C;
+const CODE_DOUBLE_ARRAY8_BIG: u8 = 0x0D;
+const CODE_DOUBLE_ARRAY8_LITTLE: u8 = 0x0E;
+const CODE_DOUBLE_ARRAY32_BIG: u8 = 0x0F;
+const CODE_DOUBLE_ARRAY32_LITTLE: u8 = 0x07;
+const CODE_DOUBLE_ARRAY64_BIG: u8 = 0x16;
+const CODE_DOUBLE_ARRAY64_LITTLE: u8 = 0x17;
+const CODE_CODEPOINTER: u8 = 0x10;
+const CODE_INFIXPOINTER: u8 = 0x11;
+const CODE_CUSTOM: u8 = 0x12;
+const CODE_CUSTOM_LEN: u8 = 0x18;
+const CODE_CUSTOM_FIXED: u8 = 0x19;
+
+/// A value in the `Marshal` format, this mirrors the layout of OCaml values without depending on
+/// the OCaml runtime, so it can be used to read and write marshaled data from Rust programs that
+/// don't link the runtime
+///
+/// ```rust
+/// use ocaml::marshal::Data;
+///
+/// let s = Data::String(b"abc".to_vec());
+/// let data = Data::Block(0, vec![Data::Int(1), s, Data::Shared(1)]);
+/// let bytes = data.to_bytes().unwrap();
+/// assert_eq!(Data::from_bytes(&bytes).unwrap(), data);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum Data {
+    /// Immediate value: `int`, `char`, `bool`, constant constructors, ...
+    Int(i64),
+
+    /// Block with a tag and fields, blocks with no fields are atoms (empty arrays)
+    Block(u8, Vec<Data>),
+
+    /// `string` or `bytes`
+    String(Vec<u8>),
+
+    /// Boxed `float`
+    Double(f64),
+
+    /// `float array` or record containing only floats, an empty `DoubleArray` is
+    /// written as the empty array atom and read back as `Block(0, vec![])`
+    DoubleArray(Vec<f64>),
+
+    /// `int32`
+    Int32(i32),
+
+    /// `int64`
+    Int64(i64),
+
+    /// `nativeint`
+    Nativeint(i64),
+
+    /// Custom block, with the custom operations identifier, the serialized data and the in-memory
+    /// size of the data on 32 and 64-bit platforms (as returned by `deserialize`)
+    Custom {
+        /// Custom operations identifier
+        id: String,
+        /// In-memory size on 32-bit platforms
+        size_32: u32,
+        /// In-memory size on 64-bit platforms
+        size_64: u64,
+        /// Serialized data
+        data: Vec<u8>,
+    },
+
+    /// Reference to a previous value, values are numbered in the order they appear (depth first)
+    /// excluding `Int` and blocks with no fields
+    Shared(usize),
+}
+
+impl Drop for Data {
+    fn drop(&mut self) {
+        // Fields are moved onto a stack before being dropped, so dropping deeply nested values
+        // doesn't recurse
+        let mut stack = match self {
+            Data::Block(_, fields) if !fields.is_empty() => core::mem::take(fields),
+            _ => return,
+        };
+        while let Some(mut x) = stack.pop() {
+            if let Data::Block(_, fields) = &mut x {
+                stack.append(fields);
+            }
+        }
+    }
+}
+
+/// Errors returned when reading or writing `Data`
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormatError {
+    /// Input ended before the end of the value
+    Truncated,
+
+    /// Input doesn't start with a `Marshal` header
+    BadMagic,
+
+    /// Input contains an unknown code
+    InvalidCode(u8),
+
+    /// Shared reference to a value that hasn't been read or written yet
+    InvalidShared,
+
+    /// Functional values can't be read
+    Closure,
+
+    /// Custom block with an unknown or invalid identifier
+    UnknownCustom(String),
+
+    /// `nativeint` with an invalid size
+    InvalidNativeint,
+
+    /// Output is too large for the `Marshal` header
+    TooLarge,
+}
+
+impl core::fmt::Display for FormatError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            FormatError::Truncated => write!(f, "truncated data"),
+            FormatError::BadMagic => write!(f, "bad magic number"),
+            FormatError::InvalidCode(c) => write!(f, "invalid code 0x{:02x}", c),
+            FormatError::InvalidShared => write!(f, "invalid shared reference"),
+            FormatError::Closure => write!(f, "functional values are not supported"),
+            FormatError::UnknownCustom(id) => write!(f, "unknown custom block {:?}", id),
+            FormatError::InvalidNativeint => write!(f, "invalid nativeint"),
+            FormatError::TooLarge => write!(f, "data is too large"),
+        }
+    }
+}
+
+impl From<FormatError> for Error {
+    fn from(x: FormatError) -> Error {
+        Error::Message(alloc::format!("marshal: {}", x).into())
+    }
+}
+
+/// Value read by `Reader::item`, the fields of blocks are read by `Reader::read`
+enum Item {
+    Data(Data),
+    Block(u8, usize),
+}
+
+struct Reader<'a, F> {
+    data: &'a [u8],
+    pos: usize,
+    objects: usize,
+    custom: F,
+}
+
+impl<'a, F: Fn(&str, &[u8]) -> Option<usize>> Reader<'a, F> {
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], FormatError> {
+        if n > self.data.len() - self.pos {
+            return Err(FormatError::Truncated);
+        }
+        let x = &self.data[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(x)
+    }
+
+    fn u8(&mut self) -> Result<u8, FormatError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, FormatError> {
+        let mut x = [0u8; 2];
+        x.copy_from_slice(self.bytes(2)?);
+        Ok(u16::from_be_bytes(x))
+    }
+
+    fn u32(&mut self) -> Result<u32, FormatError> {
+        let mut x = [0u8; 4];
+        x.copy_from_slice(self.bytes(4)?);
+        Ok(u32::from_be_bytes(x))
+    }
+
+    fn u64(&mut self) -> Result<u64, FormatError> {
+        let mut x = [0u8; 8];
+        x.copy_from_slice(self.bytes(8)?);
+        Ok(u64::from_be_bytes(x))
+    }
+
+    /// Each item takes at least one byte, so this catches invalid lengths before allocating
+    fn len(&mut self, n: u64) -> Result<usize, FormatError> {
+        if n > (self.data.len() - self.pos) as u64 {
+            return Err(FormatError::Truncated);
+        }
+        Ok(n as usize)
+    }
+
+    fn double(&mut self, big_endian: bool) -> Result<f64, FormatError> {
+        let mut x = [0u8; 8];
+        x.copy_from_slice(self.bytes(8)?);
+        if big_endian {
+            Ok(f64::from_be_bytes(x))
+        } else {
+            Ok(f64::from_le_bytes(x))
+        }
+    }
+
+    fn object(&mut self) -> usize {
+        let n = self.objects;
+        self.objects += 1;
+        n
+    }
+
+    fn shared(&mut self, ofs: u64) -> Result<Data, FormatError> {
+        if ofs == 0 || ofs > self.objects as u64 {
+            return Err(FormatError::InvalidShared);
+        }
+        Ok(Data::Shared(self.objects - ofs as usize))
+    }
+
+    fn string(&mut self, n: u64) -> Result<Data, FormatError> {
+        let n = self.len(n)?;
+        self.object();
+        Ok(Data::String(self.bytes(n)?.to_vec()))
+    }
+
+    fn double_array(&mut self, n: u64, big_endian: bool) -> Result<Data, FormatError> {
+        let n = self.len(n)?;
+        self.object();
+        let mut dest = Vec::with_capacity(n);
+        for _ in 0..n {
+            dest.push(self.double(big_endian)?);
+        }
+        Ok(Data::DoubleArray(dest))
+    }
+
+    fn block(&mut self, tag: u8, size: u64) -> Result<Item, FormatError> {
+        if size == 0 {
+            return Ok(Item::Data(Data::Block(tag, Vec::new())));
+        }
+
+        let size = self.len(size)?;
+        self.object();
+        Ok(Item::Block(tag, size))
+    }
+
+    fn custom(&mut self, code: u8) -> Result<Data, FormatError> {
+        let start = self.pos;
+        let end = match self.data[start..].iter().position(|x| *x == 0) {
+            Some(n) => start + n,
+            None => return Err(FormatError::Truncated),
+        };
+        let id = match core::str::from_utf8(&self.data[start..end]) {
+            Ok(s) => String::from(s),
+            Err(_) => return Err(FormatError::UnknownCustom(String::new())),
+        };
+        self.pos = end + 1;
+        self.object();
+
+        let sizes = if code == CODE_CUSTOM_LEN {
+            Some((self.u32()?, self.u64()?))
+        } else {
+            None
+        };
+
+        match id.as_str() {
+            "_i" => return Ok(Data::Int32(self.u32()? as i32)),
+            "_j" => return Ok(Data::Int64(self.u64()? as i64)),
+            "_n" => {
+                return match self.u8()? {
+                    1 => Ok(Data::Nativeint(self.u32()? as i32 as i64)),
+                    2 => Ok(Data::Nativeint(self.u64()? as i64)),
+                    _ => Err(FormatError::InvalidNativeint),
+                }
+            }
+            _ => (),
+        }
+
+        let (size_32, size_64) = match sizes {
+            Some(x) => x,
+            None => return Err(FormatError::UnknownCustom(id)),
+        };
+
+        let len = match (self.custom)(&id, &self.data[self.pos..]) {
+            Some(n) => n,
+            None => return Err(FormatError::UnknownCustom(id)),
+        };
+
+        Ok(Data::Custom {
+            size_32,
+            size_64,
+            data: self.bytes(len)?.to_vec(),
+            id,
+        })
+    }
+
+    /// Read a value, the fields of blocks are kept on an explicit stack instead of recursing so
+    /// deeply nested input (such as a long list) can't overflow the call stack
+    fn read(&mut self) -> Result<Data, FormatError> {
+        let mut stack: Vec<(u8, usize, Vec<Data>)> = Vec::new();
+        loop {
+            let mut x = match self.item()? {
+                Item::Data(x) => x,
+                Item::Block(tag, size) => {
+                    // Fields aren't preallocated, the size hasn't been checked against nested
+                    // blocks yet
+                    stack.push((tag, size, Vec::new()));
+                    continue;
+                }
+            };
+
+            // Complete every block that `x` was the last field of
+            loop {
+                match stack.last_mut() {
+                    None => return Ok(x),
+                    Some((_, size, fields)) => {
+                        fields.push(x);
+                        if fields.len() < *size {
+                            break;
+                        }
+                    }
+                }
+                let (tag, _, fields) = stack.pop().unwrap();
+                x = Data::Block(tag, fields);
+            }
+        }
+    }
+
+    fn item(&mut self) -> Result<Item, FormatError> {
+        let code = self.u8()?;
+        if code >= PREFIX_SMALL_BLOCK {
+            return self.block(code & 0xF, ((code >> 4) & 0x7) as u64);
+        }
+
+        match code {
+            CODE_BLOCK32 => {
+                let header = self.u32()? as u64;
+                self.block((header & 0xFF) as u8, header >> 10)
+            }
+            CODE_BLOCK64 => {
+                let header = self.u64()?;
+                self.block((header & 0xFF) as u8, header >> 10)
+            }
+            _ => self.data(code).map(Item::Data),
+        }
+    }
+
+    fn data(&mut self, code: u8) -> Result<Data, FormatError> {
+        if code >= PREFIX_SMALL_INT {
+            return Ok(Data::Int((code & 0x3F) as i64));
+        }
+
+        if code >= PREFIX_SMALL_STRING {
+            return self.string((code & 0x1F) as u64);
+        }
+
+        match code {
+            CODE_INT8 => Ok(Data::Int(self.u8()? as i8 as i64)),
+            CODE_INT16 => Ok(Data::Int(self.u16()? as i16 as i64)),
+            CODE_INT32 => Ok(Data::Int(self.u32()? as i32 as i64)),
+            CODE_INT64 => Ok(Data::Int(self.u64()? as i64)),
+            CODE_SHARED8 => {
+                let ofs = self.u8()? as u64;
+                self.shared(ofs)
+            }
+            CODE_SHARED16 => {
+                let ofs = self.u16()? as u64;
+                self.shared(ofs)
+            }
+            CODE_SHARED32 => {
+                let ofs = self.u32()? as u64;
+                self.shared(ofs)
+            }
+            CODE_SHARED64 => {
+                let ofs = self.u64()?;
+                self.shared(ofs)
+            }
+            CODE_STRING8 => {
+                let n = self.u8()? as u64;
+                self.string(n)
+            }
+            CODE_STRING32 => {
+                let n = self.u32()? as u64;
+                self.string(n)
+            }
+            CODE_STRING64 => {
+                let n = self.u64()?;
+                self.string(n)
+            }
+            CODE_DOUBLE_BIG | CODE_DOUBLE_LITTLE => {
+                self.object();
+                Ok(Data::Double(self.double(code == CODE_DOUBLE_BIG)?))
+            }
+            CODE_DOUBLE_ARRAY8_BIG | CODE_DOUBLE_ARRAY8_LITTLE => {
+                let n = self.u8()? as u64;
+                self.double_array(n, code == CODE_DOUBLE_ARRAY8_BIG)
+            }
+            CODE_DOUBLE_ARRAY32_BIG | CODE_DOUBLE_ARRAY32_LITTLE => {
+                let n = self.u32()? as u64;
+                self.double_array(n, code == CODE_DOUBLE_ARRAY32_BIG)
+            }
+            CODE_DOUBLE_ARRAY64_BIG | CODE_DOUBLE_ARRAY64_LITTLE => {
+                let n = self.u64()?;
+                self.double_array(n, code == CODE_DOUBLE_ARRAY64_BIG)
+            }
+            CODE_CUSTOM | CODE_CUSTOM_LEN | CODE_CUSTOM_FIXED => self.custom(code),
+            CODE_CODEPOINTER | CODE_INFIXPOINTER => Err(FormatError::Closure),
+            _ => Err(FormatError::InvalidCode(code)),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Writer {
+    dest: Vec<u8>,
+    objects: usize,
+    size_32: u64,
+    size_64: u64,
+}
+
+impl Writer {
+    fn code(&mut self, code: u8, n: u64, len: usize) {
+        self.dest.push(code);
+        self.dest.extend_from_slice(&n.to_be_bytes()[8 - len..]);
+    }
+
+    fn object(&mut self, size_32: u64, size_64: u64) {
+        self.objects += 1;
+        self.size_32 += 1 + size_32;
+        self.size_64 += 1 + size_64;
+    }
+
+    fn block(&mut self, tag: u8, size: u64) {
+        if tag < 16 && size < 8 {
+            self.dest
+                .push(PREFIX_SMALL_BLOCK + tag + ((size as u8) << 4));
+        } else if size < 0x40_0000 {
+            self.code(CODE_BLOCK32, (size << 10) | tag as u64, 4);
+        } else {
+            self.code(CODE_BLOCK64, (size << 10) | tag as u64, 8);
+        }
+
+        if size > 0 {
+            self.object(size, size);
+        }
+    }
+
+    /// Write a value, blocks are written using an explicit stack of the fields left to write
+    /// instead of recursing, the same as `Reader::read`
+    fn write(&mut self, data: &Data) -> Result<(), FormatError> {
+        let mut stack = alloc::vec![core::slice::from_ref(data).iter()];
+        while let Some(fields) = stack.last_mut() {
+            match fields.next() {
+                None => {
+                    stack.pop();
+                }
+                Some(Data::Block(tag, fields)) => {
+                    self.block(*tag, fields.len() as u64);
+                    stack.push(fields.iter());
+                }
+                Some(x) => self.item(x)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn item(&mut self, data: &Data) -> Result<(), FormatError> {
+        match data {
+            Data::Int(n) => {
+                let n = *n;
+                if (0..0x40).contains(&n) {
+                    self.dest.push(PREFIX_SMALL_INT + n as u8);
+                } else if (-0x80..0x80).contains(&n) {
+                    self.code(CODE_INT8, n as u64, 1);
+                } else if (-0x8000..0x8000).contains(&n) {
+                    self.code(CODE_INT16, n as u64, 2);
+                } else if (i32::MIN as i64..=i32::MAX as i64).contains(&n) {
+                    self.code(CODE_INT32, n as u64, 4);
+                } else {
+                    self.code(CODE_INT64, n as u64, 8);
+                }
+            }
+            Data::Shared(index) => {
+                if *index >= self.objects {
+                    return Err(FormatError::InvalidShared);
+                }
+                let ofs = (self.objects - index) as u64;
+                if ofs < 0x100 {
+                    self.code(CODE_SHARED8, ofs, 1);
+                } else if ofs < 0x10000 {
+                    self.code(CODE_SHARED16, ofs, 2);
+                } else if ofs < 0x1_0000_0000 {
+                    self.code(CODE_SHARED32, ofs, 4);
+                } else {
+                    self.code(CODE_SHARED64, ofs, 8);
+                }
+            }
+            Data::Block(..) => unreachable!("blocks are written by Writer::write"),
+            Data::String(s) => {
+                let len = s.len() as u64;
+                if len < 0x20 {
+                    self.dest.push(PREFIX_SMALL_STRING + len as u8);
+                } else if len < 0x100 {
+                    self.code(CODE_STRING8, len, 1);
+                } else if len < 0x1_0000_0000 {
+                    self.code(CODE_STRING32, len, 4);
+                } else {
+                    self.code(CODE_STRING64, len, 8);
+                }
+                self.dest.extend_from_slice(s);
+                self.object((len + 4) / 4, (len + 8) / 8);
+            }
+            Data::Double(f) => {
+                self.dest.push(CODE_DOUBLE_LITTLE);
+                self.dest.extend_from_slice(&f.to_le_bytes());
+                self.object(2, 1);
+            }
+            // The runtime represents every empty array as `Atom(0)`, including
+            // `[||] : float array`, so it is marshaled as an empty block
+            Data::DoubleArray(items) if items.is_empty() => self.block(0, 0),
+            Data::DoubleArray(items) => {
+                let len = items.len() as u64;
+                if len < 0x100 {
+                    self.code(CODE_DOUBLE_ARRAY8_LITTLE, len, 1);
+                } else if len < 0x1_0000_0000 {
+                    self.code(CODE_DOUBLE_ARRAY32_LITTLE, len, 4);
+                } else {
+                    self.code(CODE_DOUBLE_ARRAY64_LITTLE, len, 8);
+                }
+                for f in items {
+                    self.dest.extend_from_slice(&f.to_le_bytes());
+                }
+                self.object(len * 2, len);
+            }
+            Data::Int32(n) => {
+                self.dest.push(CODE_CUSTOM_FIXED);
+                self.dest.extend_from_slice(b"_i\0");
+                self.dest.extend_from_slice(&n.to_be_bytes());
+                self.object(2, 2);
+            }
+            Data::Int64(n) => {
+                self.dest.push(CODE_CUSTOM_FIXED);
+                self.dest.extend_from_slice(b"_j\0");
+                self.dest.extend_from_slice(&n.to_be_bytes());
+                self.object(3, 2);
+            }
+            Data::Nativeint(n) => {
+                self.dest.push(CODE_CUSTOM_LEN);
+                self.dest.extend_from_slice(b"_n\0");
+                self.dest.extend_from_slice(&4u32.to_be_bytes());
+                self.dest.extend_from_slice(&8u64.to_be_bytes());
+                if (i32::MIN as i64..=i32::MAX as i64).contains(n) {
+                    self.dest.push(1);
+                    self.dest.extend_from_slice(&(*n as i32).to_be_bytes());
+                } else {
+                    self.dest.push(2);
+                    self.dest.extend_from_slice(&n.to_be_bytes());
+                }
+                self.object(2, 2);
+            }
+            Data::Custom {
+                id,
+                size_32,
+                size_64,
+                data,
+            } => {
+                if id.as_bytes().contains(&0) {
+                    return Err(FormatError::UnknownCustom(id.clone()));
+                }
+                self.dest.push(CODE_CUSTOM_LEN);
+                self.dest.extend_from_slice(id.as_bytes());
+                self.dest.push(0);
+                self.dest.extend_from_slice(&size_32.to_be_bytes());
+                self.dest.extend_from_slice(&size_64.to_be_bytes());
+                self.dest.extend_from_slice(data);
+                self.object(1 + (*size_32 as u64).div_ceil(4), 1 + size_64.div_ceil(8));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Data {
+    /// Read a value written by `Marshal` or `Data::to_bytes`, custom blocks other than `int32`,
+    /// `int64` and `nativeint` are not supported, see `Data::from_bytes_with`
+    pub fn from_bytes(data: &[u8]) -> Result<Data, FormatError> {
+        Data::from_bytes_with(data, |_, _| None)
+    }
+
+    /// Read a value written by `Marshal` or `Data::to_bytes`. The length of the serialized data of
+    /// a custom block depends on its `deserialize` function, so `custom` is called with the
+    /// identifier and the remaining input to get the length of custom blocks other than `int32`,
+    /// `int64` and `nativeint`, returning `None` for unknown identifiers
+    pub fn from_bytes_with<F: Fn(&str, &[u8]) -> Option<usize>>(
+        data: &[u8],
+        custom: F,
+    ) -> Result<Data, FormatError> {
+        let mut reader = Reader {
+            data,
+            pos: 0,
+            objects: 0,
+            custom,
+        };
+        let header_len = match reader.u32()? {
+            MAGIC_SMALL => 20,
+            MAGIC_BIG => 32,
+            _ => return Err(FormatError::BadMagic),
+        };
+        let data_len = if header_len == 20 {
+            reader.u32()? as u64
+        } else {
+            reader.u32()?;
+            reader.u64()?
+        };
+
+        if data_len > (data.len() as u64).saturating_sub(header_len) {
+            return Err(FormatError::Truncated);
+        }
+
+        reader.pos = header_len as usize;
+        reader.data = &data[..header_len as usize + data_len as usize];
+        reader.read()
+    }
+
+    /// Write a value using the `Marshal` format, the output is the same as `Marshal.to_bytes`
+    /// (without `No_sharing`) on a little-endian 64-bit platform
+    pub fn to_bytes(&self) -> Result<Vec<u8>, FormatError> {
+        let mut w = Writer::default();
+        w.write(self)?;
+
+        if w.dest.len() as u64 >= 0x1_0000_0000 || w.size_32 >= 0x1_0000_0000 {
+            return Err(FormatError::TooLarge);
+        }
+
+        let mut dest = Vec::with_capacity(20 + w.dest.len());
+        dest.extend_from_slice(&MAGIC_SMALL.to_be_bytes());
+        dest.extend_from_slice(&(w.dest.len() as u32).to_be_bytes());
+        dest.extend_from_slice(&(w.objects as u32).to_be_bytes());
+        dest.extend_from_slice(&(w.size_32 as u32).to_be_bytes());
+        dest.extend_from_slice(&(w.size_64 as u32).to_be_bytes());
+        dest.extend_from_slice(&w.dest);
+        Ok(dest)
+    }
+}
@@ -0,0 +1,140 @@
+use crate::{sys, FromValue, Tag, ToValue, Value};
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeSet;
+use alloc::vec;
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
+use core::marker::PhantomData;
+
+/// `Root<T>` registers an OCaml value as a generational global root, keeping it alive and
+/// updating it when the garbage collector moves it. The root is removed when the `Root` is
+/// dropped.
+///
+/// This makes it possible to store OCaml values, such as closures, in long-lived Rust
+/// structures.
+///
+/// A `Root` may be moved to another thread, however like any other interaction with the OCaml
+/// runtime it should only be accessed or dropped while holding the runtime lock
+pub struct Root<T = Value>(Box<sys::Value>, PhantomData<T>);
+
+impl<T: ToValue> Root<T> {
+    /// Create a new root
+    pub fn new(x: T) -> Root<T> {
+        Root::from_value(x.to_value())
+    }
+
+    /// Replace the rooted value
+    pub fn set(&mut self, x: T) {
+        unsafe { sys::caml_modify_generational_global_root(&mut *self.0, x.to_value().0) }
+    }
+}
+
+impl<T> Root<T> {
+    pub(crate) fn from_value(value: Value) -> Root<T> {
+        let mut ptr = Box::new(value.0);
+        unsafe { sys::caml_register_generational_global_root(&mut *ptr) };
+        Root(ptr, PhantomData)
+    }
+
+    /// Get the current value without converting it
+    pub fn value(&self) -> Value {
+        Value(*self.0)
+    }
+
+    /// Borrow the current value, the result is bound to the lifetime of `rt`
+    pub fn borrow<'a>(&self, rt: &'a crate::Runtime) -> crate::Borrowed<'a, T> {
+        rt.borrow(self.value())
+    }
+}
+
+impl<T: FromValue> Root<T> {
+    /// Get the current value
+    pub fn get(&self) -> T {
+        T::from_value(self.value())
+    }
+}
+
+/// Cloning registers a new root for the same OCaml value, the value itself isn't copied
+impl<T> Clone for Root<T> {
+    fn clone(&self) -> Root<T> {
+        Root::from_value(self.value())
+    }
+}
+
+impl<T> Drop for Root<T> {
+    fn drop(&mut self) {
+        unsafe { sys::caml_remove_generational_global_root(&mut *self.0) }
+    }
+}
+
+/// Returns true when `compare` could raise `Invalid_argument` on `value`, because it contains a
+/// functional value, an abstract block or a custom block without a `compare` operation
+fn uncomparable(value: Value) -> bool {
+    let mut seen = BTreeSet::new();
+    let mut stack = vec![value];
+    while let Some(v) = stack.pop() {
+        if !v.is_block() || !seen.insert(v.0) {
+            continue;
+        }
+
+        let tag = v.tag();
+        if tag == Tag::CLOSURE || tag == Tag::INFIX || tag == Tag::ABSTRACT {
+            return true;
+        } else if tag == Tag::CUSTOM {
+            let ops = unsafe { &**(v.0 as *const *const crate::custom::CustomOps) };
+            if ops.compare.is_none() {
+                return true;
+            }
+        } else if tag.0 < Tag::NO_SCAN.0 {
+            stack.extend((0..v.size()).map(|i| v.field::<Value>(i)));
+        }
+    }
+    false
+}
+
+fn compare<T>(a: &Root<T>, b: &Root<T>) -> Ordering {
+    let (a, b) = (a.value(), b.value());
+    // `compare` raises from inside the runtime, which would skip the destructors of the caller
+    if uncomparable(a) || uncomparable(b) {
+        panic!("functional or abstract values can't be compared");
+    }
+    a.compare(b)
+}
+
+/// Roots are compared using OCaml's polymorphic `compare` and hashed using `Hashtbl.hash`, so
+/// they can be used as keys in a `BTreeMap` or `HashMap`. Since values are compared structurally,
+/// the rooted value shouldn't be mutated while it is used as a key. Comparing roots that contain
+/// functional values, abstract blocks or custom blocks without a `compare` operation panics,
+/// instead of raising `Invalid_argument` like `compare`.
+impl<T> PartialEq for Root<T> {
+    fn eq(&self, other: &Root<T>) -> bool {
+        compare(self, other) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for Root<T> {}
+
+impl<T> PartialOrd for Root<T> {
+    fn partial_cmp(&self, other: &Root<T>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Root<T> {
+    fn cmp(&self, other: &Root<T>) -> Ordering {
+        compare(self, other)
+    }
+}
+
+impl<T> Hash for Root<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_isize(self.value().hash())
+    }
+}
+
+unsafe impl<T> ToValue for Root<T> {
+    fn to_value(self) -> Value {
+        self.value()
+    }
+}
@@ -54,3 +54,123 @@ pub fn init() {
 pub fn shutdown() {
     unsafe { crate::sys::caml_shutdown() }
 }
+
+/// `Runtime` is a token representing access to the OCaml runtime, it can be used to tie the
+/// lifetime of OCaml values to the runtime lock and the garbage collector.
+///
+/// Functions that allocate take `&mut Runtime`, while `Borrowed` values hold a shared borrow of
+/// the runtime, so the compiler will reject code that keeps an unrooted value across an
+/// allocation or across `Runtime::unlocked`. Values that need to live longer should be
+/// converted to Rust types or stored in a `Root`.
+///
+/// `#[ocaml::func]` will pass the runtime handle to any argument with the type
+/// `&mut ocaml::Runtime`:
+///
+/// ```rust
+/// #[ocaml::func]
+/// pub fn hello(rt: &mut ocaml::Runtime, name: String) -> ocaml::Value {
+///     let s = rt.alloc(format!("Hello, {}", name));
+///     s.value()
+/// }
+/// ```
+///
+/// Using a value after another allocation is rejected:
+///
+/// ```rust,compile_fail
+/// fn example(rt: &mut ocaml::Runtime) -> ocaml::Value {
+///     let a = rt.alloc("a");
+///     let _b = rt.alloc("b");
+///     a.value()
+/// }
+/// ```
+pub struct Runtime {
+    _marker: core::marker::PhantomData<*const ()>,
+}
+
+impl Runtime {
+    /// Get a handle to the OCaml runtime
+    ///
+    /// # Safety
+    ///
+    /// The OCaml runtime must be initialized and the calling thread must hold the runtime lock.
+    /// There should only be one active handle at a time.
+    ///
+    /// The handle is returned by value, so values borrowed from it can't outlive the scope it's
+    /// stored in, `#[ocaml::func]` keeps it in a local variable of the generated function:
+    ///
+    /// ```rust,compile_fail
+    /// fn example() -> ocaml::Borrowed<'static> {
+    ///     let rt = unsafe { ocaml::Runtime::recover_handle() };
+    ///     rt.borrow(ocaml::Value::unit())
+    /// }
+    /// ```
+    #[inline(always)]
+    pub unsafe fn recover_handle() -> Runtime {
+        Runtime {
+            _marker: core::marker::PhantomData,
+        }
+    }
+
+    /// Convert a Rust value to OCaml, the result is only valid until the next allocation
+    pub fn alloc<T: crate::ToValue>(&mut self, x: T) -> Borrowed<'_, T> {
+        Borrowed::new(x.to_value())
+    }
+
+    /// Borrow an existing OCaml value, the result is only valid until the next allocation
+    pub fn borrow<T>(&self, value: crate::Value) -> Borrowed<'_, T> {
+        Borrowed::new(value)
+    }
+
+    /// Execute a function without the OCaml global lock, no `Borrowed` values can be used
+    /// inside of `f`
+    pub fn unlocked<T, F: FnOnce() -> T>(&mut self, f: F) -> T {
+        unlocked(f)
+    }
+}
+
+/// An OCaml value with a lifetime bound to a borrow of `Runtime`
+#[derive(Clone, Copy)]
+pub struct Borrowed<'a, T = crate::Value> {
+    value: crate::Value,
+    _marker: core::marker::PhantomData<(&'a Runtime, T)>,
+}
+
+impl<'a, T> Borrowed<'a, T> {
+    fn new(value: crate::Value) -> Borrowed<'a, T> {
+        Borrowed {
+            value,
+            _marker: core::marker::PhantomData,
+        }
+    }
+
+    /// Get the underlying value, the result is no longer tied to the runtime and may become
+    /// invalid after the next allocation
+    pub fn value(&self) -> crate::Value {
+        self.value
+    }
+
+    /// Register the value as a global root so it can outlive the borrow
+    pub fn root(&self) -> crate::Root<T> {
+        crate::Root::from_value(self.value)
+    }
+}
+
+impl<'a, T: crate::FromValue> Borrowed<'a, T> {
+    /// Convert to a Rust value
+    pub fn get(&self) -> T {
+        T::from_value(self.value)
+    }
+}
+
+impl<'a, T: crate::TryFromValue> Borrowed<'a, T> {
+    /// Convert to a Rust value, checking the layout of the OCaml value
+    pub fn try_get(&self) -> Result<T, crate::Error> {
+        T::try_from_value(self.value)
+    }
+}
+
+unsafe impl<'a, T> crate::ToValue for Borrowed<'a, T> {
+    fn to_value(self) -> crate::Value {
+        self.value
+    }
+}
@@ -1,6 +1,6 @@
 use crate as ocaml;
 
-use crate::{Error, FromValue, ToValue, Value};
+use crate::{ConversionError, Error, FromValue, ToValue, TryFromValue, Value};
 
 #[test]
 fn test_basic_array() -> Result<(), Error> {
@@ -87,3 +87,213 @@ fn test_int() {
         assert_eq!(e_, 0);
     })
 }
+
+#[test]
+fn test_try_from_value() {
+    ocaml::runtime::init();
+    ocaml::body!({
+        let a = "testing".to_value();
+        let b = 123isize.to_value();
+
+        let a_: Result<String, Error> = TryFromValue::try_from_value(a);
+        assert_eq!(a_.unwrap(), "testing");
+
+        let b_: Result<String, Error> = TryFromValue::try_from_value(b);
+        assert!(matches!(
+            b_,
+            Err(Error::Conversion(ConversionError::ExpectedBlock))
+        ));
+
+        let c_: Result<Option<isize>, Error> = TryFromValue::try_from_value(a);
+        assert!(matches!(
+            c_,
+            Err(Error::Conversion(ConversionError::InvalidTag { .. }))
+        ));
+
+        let d_: Result<(isize, isize), Error> =
+            TryFromValue::try_from_value((1isize, 2isize).to_value());
+        assert_eq!(d_.unwrap(), (1, 2));
+
+        let e_: Result<(isize, isize, isize), Error> =
+            TryFromValue::try_from_value((1isize, 2isize).to_value());
+        assert!(matches!(
+            e_,
+            Err(Error::Conversion(ConversionError::InvalidSize {
+                expected: 3,
+                found: 2
+            }))
+        ));
+    })
+}
+
+#[test]
+fn test_root() {
+    ocaml::runtime::init();
+    ocaml::body!({
+        let mut root = ocaml::Root::new(String::from("testing"));
+        unsafe { ocaml::sys::caml_minor_collection() };
+        assert_eq!(root.get(), "testing");
+
+        root.set(String::from("123"));
+        let root2 = root.clone();
+        assert_eq!(root2.value().0, root.value().0);
+        drop(root);
+        unsafe { ocaml::sys::caml_minor_collection() };
+        assert_eq!(root2.get(), "123");
+    })
+}
+
+#[test]
+fn test_compare_hash() {
+    use std::cmp::Ordering;
+
+    ocaml::runtime::init();
+    ocaml::body!({
+        let a = "abc".to_value();
+        let b = "abc".to_value();
+        let c = "abd".to_value();
+        assert_eq!(a.compare(b), Ordering::Equal);
+        assert_eq!(a.compare(c), Ordering::Less);
+        assert_eq!(c.compare(a), Ordering::Greater);
+        assert!(a.equal(b));
+        assert!(!a.equal(c));
+        assert_eq!(a.hash(), b.hash());
+
+        let nan = f64::NAN.to_value();
+        assert!(!nan.equal(nan));
+        assert_eq!(nan.compare(nan), Ordering::Equal);
+
+        let mut map = std::collections::BTreeMap::new();
+        map.insert(ocaml::Root::new((1 as ocaml::Int, "a")), 1);
+        map.insert(ocaml::Root::new((1 as ocaml::Int, "a")), 2);
+        map.insert(ocaml::Root::new((0 as ocaml::Int, "b")), 3);
+        unsafe { ocaml::sys::caml_minor_collection() };
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.values().copied().collect::<Vec<_>>(), vec![3, 2]);
+
+        let mut set = std::collections::HashSet::new();
+        set.insert(ocaml::Root::new(String::from("x")));
+        assert!(set.contains(&ocaml::Root::new(String::from("x"))));
+        assert!(!set.contains(&ocaml::Root::new(String::from("y"))));
+
+        let f: ocaml::Root = ocaml::Root::new(Value::alloc(1, ocaml::Tag::ABSTRACT));
+        let g = f.clone();
+        assert!(std::panic::catch_unwind(|| f == g).is_err());
+    })
+}
+
+#[test]
+fn test_marshal() -> Result<(), Error> {
+    use ocaml::marshal::{self, Flag};
+
+    ocaml::runtime::init();
+    ocaml::body!({
+        let a = ocaml::frame!((s, t) {
+            s = "abc".to_value();
+            t = Value::alloc(3, ocaml::Tag(0));
+            t.store_field(0, Value::int(1));
+            t.store_field(1, s);
+            t.store_field(2, s);
+            t
+        });
+        let bytes = unsafe { marshal::to_bytes(a, &[]) };
+        let no_sharing = unsafe { marshal::to_bytes(a, &[Flag::NoSharing]) };
+        assert_eq!(marshal::total_size(&bytes)?, bytes.len());
+        assert!(no_sharing.len() > bytes.len());
+
+        let b = unsafe { marshal::from_bytes(&bytes) }?;
+        assert_eq!(a.compare(b), std::cmp::Ordering::Equal);
+        let (i, x, y): (ocaml::Int, String, String) = FromValue::from_value(b);
+        assert_eq!((i, x.as_str(), y.as_str()), (1, "abc", "abc"));
+
+        assert!(unsafe { marshal::from_bytes(&bytes[..10]) }.is_err());
+        assert!(unsafe { marshal::from_bytes(&bytes[..bytes.len() - 1]) }.is_err());
+        assert!(unsafe { marshal::from_bytes(&[0u8; 32]) }.is_err());
+        Ok(())
+    })
+}
+
+#[test]
+fn test_marshal_data() -> Result<(), Error> {
+    use ocaml::marshal::{self, Data};
+
+    ocaml::runtime::init();
+    ocaml::body!({
+        let a = ocaml::frame!((s, t, x) {
+            s = "abc".to_value();
+            x = (
+                -1 as ocaml::Int,
+                1000 as ocaml::Int,
+                1.5f64,
+                vec![1.0f64, 2.0],
+                (1i32, 2i64, "a long string that doesn't fit in a small string"),
+            )
+                .to_value();
+            t = Value::alloc(4, ocaml::Tag(0));
+            t.store_field(0, s);
+            t.store_field(1, s);
+            t.store_field(2, x);
+            t.store_field(3, Value::alloc(0, ocaml::Tag(0)));
+            t
+        });
+        let bytes = unsafe { marshal::to_bytes(a, &[]) };
+        let data = Data::from_bytes(&bytes)?;
+
+        let expected = Data::Block(
+            0,
+            vec![
+                Data::String(b"abc".to_vec()),
+                Data::Shared(1),
+                Data::Block(
+                    0,
+                    vec![
+                        Data::Int(-1),
+                        Data::Int(1000),
+                        Data::Double(1.5),
+                        Data::Block(0, vec![Data::Double(1.0), Data::Double(2.0)]),
+                        Data::Block(
+                            0,
+                            vec![
+                                Data::Int32(1),
+                                Data::Int64(2),
+                                Data::String(
+                                    b"a long string that doesn't fit in a small string".to_vec(),
+                                ),
+                            ],
+                        ),
+                    ],
+                ),
+                Data::Block(0, vec![]),
+            ],
+        );
+        assert_eq!(data, expected);
+        assert_eq!(data.to_bytes()?, bytes);
+
+        let b = unsafe { marshal::from_bytes(&expected.to_bytes()?) }?;
+        assert_eq!(a.compare(b), std::cmp::Ordering::Equal);
+
+        let empty = Data::DoubleArray(vec![]).to_bytes()?;
+        assert_eq!(empty, unsafe {
+            marshal::to_bytes(Value::alloc(0, ocaml::Tag(0)), &[])
+        });
+        assert_eq!(Data::from_bytes(&empty)?, Data::Block(0, vec![]));
+
+        assert!(Data::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(Data::Shared(0).to_bytes().is_err());
+        Ok(())
+    })
+}
+
+#[test]
+fn test_marshal_data_long_list() -> Result<(), Error> {
+    use ocaml::marshal::{self, Data};
+
+    ocaml::runtime::init();
+    ocaml::body!({
+        let list: std::collections::LinkedList<ocaml::Int> = (0..100_000).collect();
+        let bytes = unsafe { marshal::to_bytes(list.to_value(), &[]) };
+        let data = Data::from_bytes(&bytes)?;
+        assert_eq!(data.to_bytes()?, bytes);
+        Ok(())
+    })
+}
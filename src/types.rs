@@ -1,6 +1,11 @@
 //! OCaml types represented in Rust, these are zero-copy and incur no additional overhead
 
-use crate::{sys, CamlError, Error};
+use crate::{
+    conv::{check_block, check_list},
+    custom::BorrowFlag,
+    error::ConversionError,
+    sys, CamlError, Error, Tag,
+};
 
 use core::{
     iter::{IntoIterator, Iterator},
@@ -8,12 +13,18 @@ use core::{
     mem, slice,
 };
 
-use crate::value::{FromValue, Size, ToValue, Value};
+use crate::value::{FromValue, Size, ToValue, TryFromValue, Value};
+use alloc::borrow::Cow;
 
 /// A handle to a Rust value/reference owned by the OCaml heap.
 ///
 /// This should only be used with values allocated with `alloc_final` or `alloc_custom`,
 /// for abstract pointers see `Value::alloc_abstract_ptr` and `Value::abstract_ptr_val`
+///
+/// When `T` implements `Custom`, `FromValue` and `TryFromValue` check that the value is a custom
+/// block created using `T`'s custom operations, `FromValue` raises `Invalid_argument` when it
+/// isn't. Other values can be converted using `Pointer::from_value_unchecked`, functions defined
+/// using `ocaml::func` accept them as arguments without checking
 #[derive(Clone, Copy, PartialEq)]
 #[repr(transparent)]
 pub struct Pointer<T>(pub Value, PhantomData<T>);
@@ -24,12 +35,78 @@ unsafe impl<T> ToValue for Pointer<T> {
     }
 }
 
-unsafe impl<T> FromValue for Pointer<T> {
+/// Check that `value` is a custom block with the custom operations for `T`
+fn check_pointer<T: crate::Custom>(value: Value) -> Result<(), Error> {
+    check_block(value, Tag::CUSTOM)?;
+
+    // The operations are usually the same static, the identifiers are compared when they aren't
+    let ops = unsafe { *(value.0 as *const *const crate::custom::CustomOps) };
+    if core::ptr::eq(ops, T::ops()) {
+        return Ok(());
+    }
+
+    let identifier = T::NAME.trim_end_matches('\0');
+    let found = crate::conv::custom_identifier(value);
+    if found != identifier {
+        return Err(ConversionError::InvalidPointer {
+            expected: core::any::type_name::<T>(),
+            identifier,
+            found,
+        }
+        .into());
+    }
+    Ok(())
+}
+
+unsafe impl<T: crate::Custom> FromValue for Pointer<T> {
     fn from_value(value: Value) -> Self {
+        if let Err(e) = check_pointer::<T>(value) {
+            // `FromValue` can't return an error, this raises `Invalid_argument`
+            let _ = Err::<(), Error>(e).to_value();
+        }
         Pointer(value, PhantomData)
     }
 }
 
+unsafe impl<T: crate::Custom> TryFromValue for Pointer<T> {
+    fn try_from_value(value: Value) -> Result<Self, Error> {
+        check_pointer::<T>(value)?;
+        Ok(Pointer(value, PhantomData))
+    }
+}
+
+/// Argument of a function defined using `ocaml::func`, `FuncArgValue` converts it using
+/// `TryFromValue` and `FuncArgPointer` is used for `Pointer<T>` when `T` doesn't implement
+/// `Custom`, since there are no custom operations to check
+#[doc(hidden)]
+pub struct FuncArg<T>(pub Value, pub PhantomData<T>);
+
+#[doc(hidden)]
+pub trait FuncArgValue {
+    type Output;
+    fn func_arg(&self) -> Result<Self::Output, Error>;
+}
+
+impl<T: TryFromValue> FuncArgValue for FuncArg<T> {
+    type Output = T;
+    fn func_arg(&self) -> Result<T, Error> {
+        T::try_from_value(self.0)
+    }
+}
+
+#[doc(hidden)]
+pub trait FuncArgPointer {
+    type Output;
+    fn func_arg(&self) -> Result<Self::Output, Error>;
+}
+
+impl<T> FuncArgPointer for &FuncArg<Pointer<T>> {
+    type Output = Pointer<T>;
+    fn func_arg(&self) -> Result<Pointer<T>, Error> {
+        Ok(Pointer(self.0, PhantomData))
+    }
+}
+
 unsafe extern "C" fn ignore(_: Value) {}
 
 impl<T> Pointer<T> {
@@ -42,24 +119,73 @@ impl<T> Pointer<T> {
         finalizer: Option<unsafe extern "C" fn(Value)>,
         used_max: Option<(usize, usize)>,
     ) -> Pointer<T> {
-        let mut ptr = Pointer::from_value(match finalizer {
+        let value = match finalizer {
             Some(f) => Value::alloc_final::<T>(f, used_max),
             None => Value::alloc_final::<T>(ignore, used_max),
-        });
+        };
+        let mut ptr = unsafe { Pointer::from_value_unchecked(value) };
         ptr.set(x);
         ptr
     }
 
+    /// Convert a value to `Pointer` without checking its custom operations, this is needed when
+    /// `T` doesn't implement `Custom`
+    ///
+    /// # Safety
+    /// `value` must be a custom block containing a `T`
+    pub unsafe fn from_value_unchecked(value: Value) -> Pointer<T> {
+        Pointer(value, PhantomData)
+    }
+
     /// Allocate a `Custom` value
     pub fn alloc_custom(x: T) -> Pointer<T>
     where
         T: crate::Custom,
     {
-        let mut ptr = Pointer::from_value(Value::alloc_custom::<T>());
+        let mut ptr = unsafe { Pointer::from_value_unchecked(Value::alloc_custom::<T>()) };
         ptr.set(x);
         ptr
     }
 
+    /// Allocate a `Custom` value using `caml_alloc_custom_mem`, the memory owned by the value is
+    /// given by `Custom::heap_size`
+    pub fn alloc_custom_mem(x: T) -> Pointer<T>
+    where
+        T: crate::Custom,
+    {
+        let mem = x.heap_size();
+        let mut ptr = unsafe { Pointer::from_value_unchecked(Value::alloc_custom_mem::<T>(mem)) };
+        ptr.set(x);
+        ptr
+    }
+
+    /// Report memory allocated by the value since it was created, `previous` is the result of
+    /// `Custom::heap_size` before the value grew. This has no effect when the value shrinks,
+    /// since the garbage collector only tracks memory that has been allocated
+    pub fn update_heap_size(&self, previous: usize)
+    where
+        T: crate::Custom,
+    {
+        let current = self.as_ref().heap_size();
+        if current <= previous {
+            return;
+        }
+
+        // This is the same limit used by `caml_alloc_custom_mem` for the major heap
+        let (heap_words, ratio) = crate::frame!((control, stat) {
+            unsafe {
+                control = Value(sys::caml_gc_get(sys::UNIT));
+                stat = Value(sys::caml_gc_quick_stat(sys::UNIT));
+            }
+            (
+                stat.field::<Value>(5).int_val() as usize,
+                control.field::<Value>(8).int_val() as usize,
+            )
+        });
+        let max = heap_words * mem::size_of::<Value>() / 150 * ratio;
+        unsafe { sys::caml_adjust_gc_speed(current - previous, max) }
+    }
+
     /// Drop pointer in place
     ///
     /// # Safety
@@ -99,6 +225,94 @@ impl<T> AsMut<T> for Pointer<T> {
     }
 }
 
+impl<T: crate::Custom> Pointer<T> {
+    fn check_borrow_flag() -> Result<(), Error> {
+        if !T::BORROW_CHECKED {
+            return Err(Error::Message(Cow::Borrowed(
+                "Pointer: borrow checking isn't enabled, see Custom::BORROW_CHECKED",
+            )));
+        }
+        Ok(())
+    }
+
+    fn borrow_flag(&self) -> &BorrowFlag {
+        unsafe {
+            let data = self.0.custom_ptr_val::<u8>();
+            &*(data.add(crate::custom::borrow_flag_offset::<T>()) as *const BorrowFlag)
+        }
+    }
+
+    /// Immutably borrow the value, the same value may be passed to a function more than once so
+    /// this returns an error instead of aliasing a mutable borrow. This can only be used with
+    /// values allocated for a `Custom` type with `BORROW_CHECKED` set
+    pub fn borrow(&self) -> Result<PointerRef<'_, T>, Error> {
+        Self::check_borrow_flag()?;
+        let flag = self.borrow_flag();
+        if flag.get() < 0 {
+            return Err(Error::Message(Cow::Borrowed(
+                "Pointer: value is already mutably borrowed",
+            )));
+        }
+        flag.set(flag.get() + 1);
+        Ok(PointerRef(self))
+    }
+
+    /// Mutably borrow the value, returns an error if the value is already borrowed. This can only
+    /// be used with values allocated for a `Custom` type with `BORROW_CHECKED` set
+    pub fn borrow_mut(&self) -> Result<PointerMut<'_, T>, Error> {
+        Self::check_borrow_flag()?;
+        let flag = self.borrow_flag();
+        if flag.get() != 0 {
+            return Err(Error::Message(Cow::Borrowed(
+                "Pointer: value is already borrowed",
+            )));
+        }
+        flag.set(-1);
+        Ok(PointerMut(self))
+    }
+}
+
+/// Immutable borrow of a `Pointer`, created using `Pointer::borrow`
+pub struct PointerRef<'a, T: crate::Custom>(&'a Pointer<T>);
+
+impl<'a, T: crate::Custom> core::ops::Deref for PointerRef<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.0.as_ptr() }
+    }
+}
+
+impl<'a, T: crate::Custom> Drop for PointerRef<'a, T> {
+    fn drop(&mut self) {
+        let flag = self.0.borrow_flag();
+        flag.set(flag.get() - 1);
+    }
+}
+
+/// Mutable borrow of a `Pointer`, created using `Pointer::borrow_mut`
+pub struct PointerMut<'a, T: crate::Custom>(&'a Pointer<T>);
+
+impl<'a, T: crate::Custom> core::ops::Deref for PointerMut<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.0.as_ptr() }
+    }
+}
+
+impl<'a, T: crate::Custom> core::ops::DerefMut for PointerMut<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.0 .0.custom_ptr_val_mut() }
+    }
+}
+
+impl<'a, T: crate::Custom> Drop for PointerMut<'a, T> {
+    fn drop(&mut self) {
+        self.0.borrow_flag().set(0);
+    }
+}
+
 /// `Array<A>` wraps an OCaml `'a array` without converting it to Rust
 #[derive(Clone, Copy, PartialEq)]
 #[repr(transparent)]
@@ -116,6 +330,17 @@ unsafe impl<T: ToValue + FromValue> FromValue for Array<T> {
     }
 }
 
+unsafe impl<T: ToValue + FromValue> TryFromValue for Array<T> {
+    fn try_from_value(value: Value) -> Result<Self, Error> {
+        if value.is_block() && value.tag() == Tag::DOUBLE_ARRAY {
+            return Ok(Array(value, PhantomData));
+        }
+
+        check_block(value, Tag(0))?;
+        Ok(Array(value, PhantomData))
+    }
+}
+
 impl<'a> Array<f64> {
     /// Set value to double array
     pub fn set_double(&mut self, i: usize, f: f64) -> Result<(), Error> {
@@ -265,6 +490,13 @@ unsafe impl<T: ToValue + FromValue> FromValue for List<T> {
     }
 }
 
+unsafe impl<T: ToValue + FromValue> TryFromValue for List<T> {
+    fn try_from_value(value: Value) -> Result<Self, Error> {
+        check_list(value)?;
+        Ok(List(value, PhantomData))
+    }
+}
+
 impl<T: ToValue + FromValue> List<T> {
     /// An empty list
     #[inline(always)]
@@ -371,6 +603,95 @@ impl<T: ToValue + FromValue> Iterator for ListIterator<T> {
     }
 }
 
+fn check_closure(value: Value) -> Result<(), Error> {
+    // Mutually recursive functions are represented using infix pointers into a single closure
+    if value.is_block() && value.tag() == Tag::INFIX {
+        return Ok(());
+    }
+
+    check_block(value, Tag::CLOSURE)
+}
+
+macro_rules! closure_impl {
+    ($(#[$meta:meta])* $name:ident, $n:literal, $($t:ident: $x:ident: $v:ident),*) => {
+        $(#[$meta])*
+        ///
+        /// All arguments are passed at once using `caml_callbackN`, which handles partial
+        /// application (the result type should be another closure type) and over-application
+        /// (a closure returning another closure is applied to the remaining arguments)
+        #[repr(transparent)]
+        pub struct $name<$($t,)* R>(Value, PhantomData<fn($($t),*) -> R>);
+
+        impl<$($t,)* R> Clone for $name<$($t,)* R> {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl<$($t,)* R> Copy for $name<$($t,)* R> {}
+
+        unsafe impl<$($t,)* R> ToValue for $name<$($t,)* R> {
+            fn to_value(self) -> Value {
+                self.0
+            }
+        }
+
+        unsafe impl<$($t,)* R> FromValue for $name<$($t,)* R> {
+            fn from_value(value: Value) -> Self {
+                $name(value, PhantomData)
+            }
+        }
+
+        unsafe impl<$($t,)* R> TryFromValue for $name<$($t,)* R> {
+            fn try_from_value(value: Value) -> Result<Self, Error> {
+                check_closure(value)?;
+                Ok($name(value, PhantomData))
+            }
+        }
+
+        impl<$($t: ToValue,)* R: FromValue> $name<$($t,)* R> {
+            /// Call the closure, OCaml exceptions are returned as `CamlError`
+            pub fn call(&self, $($x: $t),*) -> Result<R, Error> {
+                let res = crate::frame!((f, $($v),*) {
+                    f = self.0;
+                    $($v = $x.to_value();)*
+                    let mut args = [$($v.0),*];
+                    unsafe { Value(sys::caml_callbackN_exn(f.0, $n, args.as_mut_ptr())) }
+                });
+
+                if res.is_exception_result() {
+                    let e = res.exception().unwrap();
+                    return Err(CamlError::from_exception(e).into());
+                }
+
+                Ok(R::from_value(res))
+            }
+        }
+    };
+}
+
+closure_impl!(
+    /// `Fn1<A, R>` wraps an OCaml closure with the type `'a -> 'r`
+    Fn1, 1, A: a: va
+);
+closure_impl!(
+    /// `Fn2<A, B, R>` wraps an OCaml closure with the type `'a -> 'b -> 'r`
+    Fn2, 2, A: a: va, B: b: vb
+);
+closure_impl!(
+    /// `Fn3<A, B, C, R>` wraps an OCaml closure with the type `'a -> 'b -> 'c -> 'r`
+    Fn3, 3, A: a: va, B: b: vb, C: c: vc
+);
+closure_impl!(
+    /// `Fn4<A, B, C, D, R>` wraps an OCaml closure with the type `'a -> 'b -> 'c -> 'd -> 'r`
+    Fn4, 4, A: a: va, B: b: vb, C: c: vc, D: d: vd
+);
+closure_impl!(
+    /// `Fn5<A, B, C, D, E, R>` wraps an OCaml closure with the type
+    /// `'a -> 'b -> 'c -> 'd -> 'e -> 'r`
+    Fn5, 5, A: a: va, B: b: vb, C: c: vc, D: d: vd, E: e: ve
+);
+
 /// `bigarray` contains wrappers for OCaml `Bigarray` values. These types can be used to transfer arrays of numbers between Rust
 /// and OCaml directly without the allocation overhead of an `array` or `list`
 pub mod bigarray {
@@ -398,6 +719,68 @@ pub mod bigarray {
         };
     }
 
+    /// Bigarray memory layout
+    pub trait Layout: Copy + PartialEq {
+        /// OCaml bigarray layout flag
+        fn layout() -> i32;
+    }
+
+    /// C layout, arrays are stored in row-major order and OCaml indices start at 0. This is the
+    /// default layout
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct CLayout;
+
+    /// Fortran layout, arrays are stored in column-major order and OCaml indices start at 1
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct FortranLayout;
+
+    impl Layout for CLayout {
+        fn layout() -> i32 {
+            bigarray::Layout::C_LAYOUT as i32
+        }
+    }
+
+    impl Layout for FortranLayout {
+        fn layout() -> i32 {
+            bigarray::Layout::FORTRAN_LAYOUT as i32
+        }
+    }
+
+    /// Returns true when `L` is `FortranLayout`
+    pub(crate) fn is_fortran<L: Layout>() -> bool {
+        L::layout() == bigarray::Layout::FORTRAN_LAYOUT as i32
+    }
+
+    fn layout_name(layout: i32) -> &'static str {
+        if layout == bigarray::Layout::FORTRAN_LAYOUT as i32 {
+            "fortran_layout"
+        } else {
+            "c_layout"
+        }
+    }
+
+    /// Complex number, used for `Bigarray.complex32` and `Bigarray.complex64` elements. This has
+    /// the same layout as OCaml's `Complex.t` and `num_complex::Complex`
+    #[repr(C)]
+    #[derive(Debug, Clone, Copy, PartialEq, Default)]
+    pub struct Complex<T> {
+        /// Real part
+        pub re: T,
+        /// Imaginary part
+        pub im: T,
+    }
+
+    /// OCaml integer stored in a `Bigarray.int` array, the value is stored untagged so it has the
+    /// same range as `Int`
+    #[repr(transparent)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+    pub struct CamlInt(pub crate::Int);
+
+    /// Byte stored in a `Bigarray.char` array
+    #[repr(transparent)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+    pub struct Char(pub u8);
+
     make_kind!(u8, UINT8);
     make_kind!(i8, SINT8);
     make_kind!(u16, UINT16);
@@ -406,53 +789,286 @@ pub mod bigarray {
     make_kind!(f64, FLOAT64);
     make_kind!(i64, INT64);
     make_kind!(i32, INT32);
-    make_kind!(char, CHAR);
+    make_kind!(isize, NATIVE_INT);
+    make_kind!(CamlInt, CAML_INT);
+    make_kind!(Char, CHAR);
+    make_kind!(Complex<f32>, COMPLEX32);
+    make_kind!(Complex<f64>, COMPLEX64);
+
+    /// Maximum number of dimensions of a bigarray
+    pub const MAX_DIMS: usize = 16;
+
+    /// Returns the name of the OCaml element type for a bigarray kind
+    fn kind_name(kind: i32) -> &'static str {
+        const NAMES: [&str; 13] = [
+            "float32",
+            "float64",
+            "int8_signed",
+            "int8_unsigned",
+            "int16_signed",
+            "int16_unsigned",
+            "int32",
+            "int64",
+            "int",
+            "nativeint",
+            "complex32",
+            "complex64",
+            "char",
+        ];
+        NAMES.get(kind as usize).copied().unwrap_or("<unknown>")
+    }
+
+    /// Check that `value` is a bigarray with elements of type `T`, layout `L` and `rank`
+    /// dimensions, any number of dimensions is accepted when `rank` is `None`
+    pub(crate) fn check_bigarray<T: Kind, L: Layout>(
+        value: Value,
+        rank: Option<usize>,
+    ) -> Result<(), Error> {
+        check_block(value, Tag::CUSTOM)?;
+
+        let found = crate::conv::custom_identifier(value);
+        if !found.starts_with("_bigarr") {
+            return Err(ConversionError::InvalidCustom {
+                expected: "_bigarr02",
+                found,
+            }
+            .into());
+        }
+
+        let ba = value.custom_ptr_val::<bigarray::Bigarray>();
+        let found = unsafe { (*ba).num_dims } as usize;
+        if let Some(rank) = rank.filter(|rank| *rank != found) {
+            return Err(ConversionError::InvalidRank {
+                expected: rank,
+                found,
+            }
+            .into());
+        }
+
+        let found = unsafe { (*ba).flags } as i32 & bigarray::Kind::KIND_MASK as i32;
+        if found != T::kind() {
+            return Err(ConversionError::InvalidKind {
+                expected: kind_name(T::kind()),
+                found: kind_name(found),
+            }
+            .into());
+        }
+
+        let found = unsafe { (*ba).flags } as i32 & bigarray::LAYOUT_MASK;
+        if found != L::layout() {
+            return Err(ConversionError::InvalidLayout {
+                expected: layout_name(L::layout()),
+                found: layout_name(found),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+
+    /// Used by `FromValue`, raises `Invalid_argument` when `check_bigarray` fails
+    pub(crate) fn expect_bigarray<T: Kind, L: Layout>(value: Value, rank: Option<usize>) {
+        if let Err(e) = check_bigarray::<T, L>(value, rank) {
+            Error::raise_argument_error("bigarray", e)
+        }
+    }
 
     /// OCaml Bigarray.Array1 type, this introduces no
     /// additional overhead compared to a `Value` type
     #[repr(transparent)]
     #[derive(Clone, Copy, PartialEq)]
-    pub struct Array1<T>(Value, PhantomData<T>);
+    pub struct Array1<T, L = CLayout>(Value, PhantomData<(T, L)>);
 
-    unsafe impl<T> crate::FromValue for Array1<T> {
-        fn from_value(value: Value) -> Array1<T> {
+    unsafe impl<T: Kind, L: Layout> crate::FromValue for Array1<T, L> {
+        fn from_value(value: Value) -> Array1<T, L> {
+            expect_bigarray::<T, L>(value, Some(1));
             Array1(value, PhantomData)
         }
     }
 
-    unsafe impl<T> crate::ToValue for Array1<T> {
+    unsafe impl<T: Kind, L: Layout> crate::TryFromValue for Array1<T, L> {
+        fn try_from_value(value: Value) -> Result<Array1<T, L>, Error> {
+            check_bigarray::<T, L>(value, Some(1))?;
+            Ok(Array1(value, PhantomData))
+        }
+    }
+
+    unsafe impl<T, L> crate::ToValue for Array1<T, L> {
         fn to_value(self) -> Value {
             self.0
         }
     }
 
     #[cfg(not(feature = "no-std"))]
-    impl<T: Copy + Kind> From<&'static [T]> for Array1<T> {
-        fn from(x: &'static [T]) -> Array1<T> {
+    impl<T: Copy + Kind, L: Layout> From<&'static [T]> for Array1<T, L> {
+        fn from(x: &'static [T]) -> Array1<T, L> {
             Array1::from_slice(x)
         }
     }
 
     #[cfg(not(feature = "no-std"))]
-    impl<T: Copy + Kind> From<Vec<T>> for Array1<T> {
-        fn from(x: Vec<T>) -> Array1<T> {
-            let mut arr = Array1::<T>::create(x.len());
-            let data = arr.data_mut();
-            data.copy_from_slice(x.as_slice());
-            arr
+    impl<T: Copy + Kind, L: Layout> From<Vec<T>> for Array1<T, L> {
+        fn from(x: Vec<T>) -> Array1<T, L> {
+            Array1::from_vec(x)
+        }
+    }
+
+    /// Size and alignment of bigarray elements, used to free arrays created from Rust
+    /// allocations
+    fn element_layout(flags: sys::Intnat) -> core::alloc::Layout {
+        let kind = flags as i32 & bigarray::Kind::KIND_MASK as i32;
+        let (size, align) = match kind {
+            x if x == bigarray::Kind::FLOAT32 as i32 => (4, 4),
+            x if x == bigarray::Kind::FLOAT64 as i32 => (8, mem::align_of::<f64>()),
+            x if x == bigarray::Kind::SINT16 as i32 || x == bigarray::Kind::UINT16 as i32 => (2, 2),
+            x if x == bigarray::Kind::INT32 as i32 => (4, 4),
+            x if x == bigarray::Kind::INT64 as i32 => (8, mem::align_of::<i64>()),
+            x if x == bigarray::Kind::CAML_INT as i32 || x == bigarray::Kind::NATIVE_INT as i32 => {
+                (mem::size_of::<isize>(), mem::align_of::<isize>())
+            }
+            x if x == bigarray::Kind::COMPLEX32 as i32 => (8, 4),
+            x if x == bigarray::Kind::COMPLEX64 as i32 => (16, mem::align_of::<f64>()),
+            _ => (1, 1),
+        };
+        unsafe { core::alloc::Layout::from_size_align_unchecked(size, align) }
+    }
+
+    /// Free data allocated by Rust, the size is also removed from the memory the GC is tracking
+    /// for `alloc_mapped`
+    unsafe fn free_owned(data: bigarray::Data, len: usize, elem: core::alloc::Layout) {
+        // Empty boxed slices don't own an allocation
+        if len == 0 || elem.size() == 0 {
+            return;
+        }
+        let size = elem.size() * len;
+        let layout = core::alloc::Layout::from_size_align_unchecked(size, elem.align());
+        alloc::alloc::dealloc(data as *mut u8, layout);
+        sys::caml_free_dependent_memory(size as sys::Uintnat);
+    }
+
+    /// Finalizer for arrays created from Rust allocations, this is the same as the finalizer for
+    /// memory mapped files except the data is freed using the Rust allocator
+    unsafe extern "C" fn finalize_owned(v: sys::Value) {
+        let ba = Value(v).custom_ptr_val_mut::<bigarray::Bigarray>();
+        let elem = element_layout((*ba).flags);
+        let proxy = (*ba).proxy as *mut bigarray::BigarrayProxy;
+        if proxy.is_null() {
+            let dims = slice::from_raw_parts((*ba).dim.as_ptr(), (*ba).num_dims as usize);
+            let len = dims.iter().map(|x| *x as usize).product();
+            free_owned((*ba).data, len, elem);
+        } else {
+            (*proxy).refcount -= 1;
+            if (*proxy).refcount == 0 {
+                free_owned((*proxy).data, (*proxy).size / elem.size(), elem);
+                bigarray::free(proxy as bigarray::Data);
+            }
+        }
+    }
+
+    static OWNED_OPS: core::sync::atomic::AtomicPtr<sys::custom_operations> =
+        core::sync::atomic::AtomicPtr::new(core::ptr::null_mut());
+
+    /// Custom operations for arrays created from Rust allocations, these are the bigarray
+    /// operations with a different finalizer. They are allocated once and never freed, if two
+    /// threads initialize them at the same time the loser frees its copy
+    fn owned_ops() -> *mut sys::custom_operations {
+        use core::sync::atomic::Ordering;
+
+        let ops = OWNED_OPS.load(Ordering::Acquire);
+        if !ops.is_null() {
+            return ops;
         }
+
+        let mut new = alloc::boxed::Box::new(unsafe { bigarray::caml_ba_ops });
+        new.finalize = Some(finalize_owned);
+        let new = alloc::boxed::Box::into_raw(new);
+        match OWNED_OPS.compare_exchange(
+            core::ptr::null_mut(),
+            new,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        ) {
+            Ok(_) => new,
+            Err(ops) => {
+                drop(unsafe { alloc::boxed::Box::from_raw(new) });
+                ops
+            }
+        }
+    }
+
+    unsafe fn set_ops(v: Value, ops: *mut sys::custom_operations) {
+        *(v.0 as *mut *mut sys::custom_operations) = ops
+    }
+
+    /// Allocate a bigarray using `owned_ops` that takes ownership of `data`
+    ///
+    /// Arrays are marked as memory mapped so the runtime creates a proxy for sub-arrays instead
+    /// of sharing the data without tracking it. This relies on sub-arrays inheriting the custom
+    /// operations of their parent (`caml_ba_sub`, `caml_ba_slice` and `caml_ba_reshape`), so a
+    /// memory mapped array is never passed to the default bigarray finalizer.
+    ///
+    /// The runtime doesn't count the data of memory mapped arrays, so it is added to the memory
+    /// the GC is tracking using `caml_alloc_dependent_memory` and removed by `finalize_owned`
+    unsafe fn alloc_mapped<T: Kind, L: Layout>(
+        data: alloc::boxed::Box<[T]>,
+        dims: &[sys::Intnat],
+    ) -> Value {
+        let size = mem::size_of_val(&*data);
+        let ptr = alloc::boxed::Box::into_raw(data) as *mut T;
+        if size > 0 {
+            sys::caml_alloc_dependent_memory(size as sys::Uintnat);
+        }
+        crate::frame!((x) {
+            x = Value(bigarray::caml_ba_alloc(
+                T::kind() | L::layout() | bigarray::Managed::MAPPED_FILE as i32,
+                dims.len() as i32,
+                ptr as bigarray::Data,
+                dims.as_ptr(),
+            ));
+            set_ops(x, owned_ops());
+            x
+        })
+    }
+
+    /// Allocate a bigarray that takes ownership of `data`, `dims` should have at most
+    /// `MAX_DIMS` items and their product must be `data.len()`. Before OCaml 4.10 the runtime
+    /// doesn't copy custom operations to sub-arrays, so the data is copied into a managed array
+    /// instead
+    pub(crate) fn alloc_owned<T: Kind, L: Layout>(
+        data: alloc::boxed::Box<[T]>,
+        dims: &[sys::Intnat],
+    ) -> Value {
+        if bigarray::SUB_INHERITS_OPS {
+            return unsafe { alloc_mapped::<T, L>(data, dims) };
+        }
+
+        crate::frame!((x) {
+            x = unsafe {
+                Value(bigarray::caml_ba_alloc(
+                    T::kind() | L::layout() | bigarray::Managed::MANAGED as i32,
+                    dims.len() as i32,
+                    core::ptr::null_mut(),
+                    dims.as_ptr(),
+                ))
+            };
+            let ba = x.custom_ptr_val::<bigarray::Bigarray>();
+            unsafe {
+                core::ptr::copy_nonoverlapping(data.as_ptr(), (*ba).data as *mut T, data.len());
+            }
+            x
+        })
     }
 
-    impl<T: Copy + Kind> Array1<T> {
+    impl<T: Copy + Kind, L: Layout> Array1<T, L> {
         /// Array1::of_slice is used to convert from a slice to OCaml Bigarray,
         /// the `data` parameter must outlive the resulting bigarray or there is
         /// no guarantee the data will be valid. Use `Array1::from_slice` to clone the
-        /// contents of a slice.
-        pub fn of_slice(data: &mut [T]) -> Array1<T> {
+        /// contents of a slice, or `Array1::from_vec` to move a `Vec` without copying.
+        pub fn of_slice(data: &mut [T]) -> Array1<T, L> {
             let x = crate::frame!((x) {
                 x = unsafe {
                     Value(bigarray::caml_ba_alloc_dims(
-                        T::kind() | bigarray::Managed::EXTERNAL as i32,
+                        T::kind() | L::layout() | bigarray::Managed::EXTERNAL as i32,
                         1,
                         data.as_mut_ptr() as bigarray::Data,
                         data.len() as sys::Intnat,
@@ -466,17 +1082,35 @@ pub mod bigarray {
         /// Convert from a slice to OCaml Bigarray, copying the array. This is the implemtation
         /// used by `Array1::from` for slices to avoid any potential lifetime issues
         #[cfg(not(feature = "no-std"))]
-        pub fn from_slice(data: &[T]) -> Array1<T> {
+        pub fn from_slice(data: &[T]) -> Array1<T, L> {
             Array1::from(data.to_vec())
         }
 
+        /// Move a `Vec` into an OCaml Bigarray without copying the data, the `Vec` is freed when
+        /// the bigarray (and any sub-arrays created from it) are garbage collected. This is the
+        /// implementation used by `Array1::from` for `Vec`
+        ///
+        /// Any unused capacity is released first, which may reallocate the `Vec`. The data is
+        /// copied before OCaml 4.10, where sub-arrays don't inherit the custom operations of their
+        /// parent, since the runtime would otherwise free it using `free`
+        pub fn from_vec(data: alloc::vec::Vec<T>) -> Array1<T, L> {
+            Array1::from_boxed_slice(data.into_boxed_slice())
+        }
+
+        /// Move a boxed slice into an OCaml Bigarray without copying the data, see
+        /// `Array1::from_vec`
+        pub fn from_boxed_slice(data: alloc::boxed::Box<[T]>) -> Array1<T, L> {
+            let len = data.len() as sys::Intnat;
+            Array1(alloc_owned::<T, L>(data, &[len]), PhantomData)
+        }
+
         /// Create a new OCaml `Bigarray.Array1` with the given type and size
-        pub fn create(n: Size) -> Array1<T> {
+        pub fn create(n: Size) -> Array1<T, L> {
             let x = crate::frame!((x) {
                 let data = unsafe { bigarray::malloc(n * mem::size_of::<T>()) };
                 x = unsafe {
                     Value(bigarray::caml_ba_alloc_dims(
-                        T::kind() | bigarray::Managed::MANAGED as i32,
+                        T::kind() | L::layout() | bigarray::Managed::MANAGED as i32,
                         1,
                         data,
                         n as sys::Intnat,
@@ -510,6 +1144,78 @@ pub mod bigarray {
             let ba = self.0.custom_ptr_val::<bigarray::Bigarray>();
             unsafe { slice::from_raw_parts_mut((*ba).data as *mut T, self.len()) }
         }
+
+        /// Returns the `len` items starting at `offset` as a new bigarray that shares its data
+        /// with `self`, like `Bigarray.Array1.sub`. `offset` starts at 0 for both layouts. The
+        /// data stays alive until both arrays have been garbage collected
+        pub fn sub(&self, offset: usize, len: usize) -> Result<Array1<T, L>, Error> {
+            if offset > self.len() || len > self.len() - offset {
+                return Err(
+                    CamlError::InvalidArgument("Bigarray.sub: bad sub-array".into()).into(),
+                );
+            }
+
+            // Fortran arrays are indexed from 1 in OCaml
+            let offset = if is_fortran::<L>() {
+                offset + 1
+            } else {
+                offset
+            };
+            let x = crate::frame!((x, parent) {
+                parent = self.0;
+                x = unsafe {
+                    Value(bigarray::caml_ba_sub(
+                        parent.0,
+                        Value::int(offset as crate::Int).0,
+                        Value::int(len as crate::Int).0,
+                    ))
+                };
+                x
+            });
+            Ok(Array1(x, PhantomData))
+        }
+
+        /// Copy the contents of `self` into `dst`, like `Bigarray.Array1.blit`. The arrays may
+        /// overlap, an error is returned when they have different lengths
+        pub fn blit(&self, dst: &mut Array1<T, L>) -> Result<(), Error> {
+            if self.len() != dst.len() {
+                return Err(
+                    CamlError::InvalidArgument("Bigarray.blit: dimension mismatch".into()).into(),
+                );
+            }
+            unsafe {
+                bigarray::caml_ba_blit((self.0).0, (dst.0).0);
+            }
+            Ok(())
+        }
+
+        /// Copy `src` into `self`, returns an error when `src` has a different length
+        pub fn blit_from_slice(&mut self, src: &[T]) -> Result<(), Error> {
+            if src.len() != self.len() {
+                return Err(CamlError::InvalidArgument(
+                    "Bigarray.blit_from_slice: dimension mismatch".into(),
+                )
+                .into());
+            }
+            // `src` may point into the same data if it was borrowed from a sub-array
+            let ba = self.0.custom_ptr_val::<bigarray::Bigarray>();
+            unsafe { core::ptr::copy(src.as_ptr(), (*ba).data as *mut T, src.len()) }
+            Ok(())
+        }
+
+        /// Copy the contents of `self` into `dst`, returns an error when `dst` has a different
+        /// length
+        pub fn copy_to_slice(&self, dst: &mut [T]) -> Result<(), Error> {
+            if dst.len() != self.len() {
+                return Err(CamlError::InvalidArgument(
+                    "Bigarray.copy_to_slice: dimension mismatch".into(),
+                )
+                .into());
+            }
+            let ba = self.0.custom_ptr_val::<bigarray::Bigarray>();
+            unsafe { core::ptr::copy((*ba).data as *const T, dst.as_mut_ptr(), dst.len()) }
+            Ok(())
+        }
     }
 
     #[cfg(all(feature = "bigarray-ext", not(feature = "no-std")))]
@@ -518,33 +1224,41 @@ pub mod bigarray {
 
 #[cfg(all(feature = "bigarray-ext", not(feature = "no-std")))]
 pub(crate) mod bigarray_ext {
-    use ndarray::{ArrayView2, ArrayView3, ArrayViewMut2, ArrayViewMut3, Dimension};
+    use ndarray::{
+        ArrayView2, ArrayView3, ArrayViewD, ArrayViewMut2, ArrayViewMut3, ArrayViewMutD, Dimension,
+        IxDyn, ShapeBuilder,
+    };
 
     use core::{marker::PhantomData, mem, ptr, slice};
 
     use crate::{
-        bigarray::Kind,
+        bigarray::{
+            alloc_owned, check_bigarray, expect_bigarray, is_fortran, CLayout, Kind, Layout,
+            MAX_DIMS,
+        },
         sys::{self, bigarray},
-        FromValue, ToValue, Value,
+        CamlError, ConversionError, Error, FromValue, ToValue, TryFromValue, Value,
     };
 
     /// OCaml Bigarray.Array2 type, this introduces no
     /// additional overhead compared to a `Value` type
     #[repr(transparent)]
     #[derive(Clone, Copy, PartialEq)]
-    pub struct Array2<T>(Value, PhantomData<T>);
+    pub struct Array2<T, L = CLayout>(Value, PhantomData<(T, L)>);
 
-    impl<T: Copy + Kind> Array2<T> {
+    impl<T: Copy + Kind, L: Layout> Array2<T, L> {
         /// Returns array view
         pub fn view(&self) -> ArrayView2<T> {
             let ba = self.0.custom_ptr_val::<bigarray::Bigarray>();
-            unsafe { ArrayView2::from_shape_ptr(self.shape(), (*ba).data as *const T) }
+            let shape = self.shape().set_f(is_fortran::<L>());
+            unsafe { ArrayView2::from_shape_ptr(shape, (*ba).data as *const T) }
         }
 
         /// Returns mutable array view
         pub fn view_mut(&mut self) -> ArrayViewMut2<T> {
             let ba = self.0.custom_ptr_val::<bigarray::Bigarray>();
-            unsafe { ArrayViewMut2::from_shape_ptr(self.shape(), (*ba).data as *mut T) }
+            let shape = self.shape().set_f(is_fortran::<L>());
+            unsafe { ArrayViewMut2::from_shape_ptr(shape, (*ba).data as *mut T) }
         }
 
         /// Returns the shape of `self`
@@ -570,26 +1284,34 @@ pub(crate) mod bigarray_ext {
         }
     }
 
-    unsafe impl<T> FromValue for Array2<T> {
-        fn from_value(value: Value) -> Array2<T> {
+    unsafe impl<T: Kind, L: Layout> FromValue for Array2<T, L> {
+        fn from_value(value: Value) -> Array2<T, L> {
+            expect_bigarray::<T, L>(value, Some(2));
             Array2(value, PhantomData)
         }
     }
 
-    unsafe impl<T> ToValue for Array2<T> {
+    unsafe impl<T: Kind, L: Layout> TryFromValue for Array2<T, L> {
+        fn try_from_value(value: Value) -> Result<Array2<T, L>, Error> {
+            check_bigarray::<T, L>(value, Some(2))?;
+            Ok(Array2(value, PhantomData))
+        }
+    }
+
+    unsafe impl<T, L> ToValue for Array2<T, L> {
         fn to_value(self) -> Value {
             self.0
         }
     }
 
-    impl<T: Copy + Kind> Array2<T> {
+    impl<T: Copy + Kind, L: Layout> Array2<T, L> {
         /// Create a new OCaml `Bigarray.Array2` with the given type and shape
-        pub fn create(dim: ndarray::Ix2) -> Array2<T> {
+        pub fn create(dim: ndarray::Ix2) -> Array2<T, L> {
             let x = crate::frame!((x) {
                 let data = unsafe { bigarray::malloc(dim.size() * mem::size_of::<T>()) };
                 x = unsafe {
                     Value(bigarray::caml_ba_alloc_dims(
-                        T::kind() | bigarray::Managed::MANAGED as i32,
+                        T::kind() | L::layout() | bigarray::Managed::MANAGED as i32,
                         2,
                         data,
                         dim[0] as sys::Intnat,
@@ -602,14 +1324,10 @@ pub(crate) mod bigarray_ext {
         }
     }
 
-    impl<T: Copy + Kind> From<ndarray::Array2<T>> for Array2<T> {
-        fn from(data: ndarray::Array2<T>) -> Array2<T> {
-            let dim = data.raw_dim();
-            let array = Array2::create(dim);
-            let ba = array.0.custom_ptr_val::<bigarray::Bigarray>();
-            unsafe {
-                ptr::copy_nonoverlapping(data.as_ptr(), (*ba).data as *mut T, dim.size());
-            }
+    impl<T: Copy + Kind, L: Layout> From<ndarray::Array2<T>> for Array2<T, L> {
+        fn from(data: ndarray::Array2<T>) -> Array2<T, L> {
+            let mut array = Array2::create(data.raw_dim());
+            array.view_mut().assign(&data);
             array
         }
     }
@@ -618,19 +1336,21 @@ pub(crate) mod bigarray_ext {
     /// additional overhead compared to a `Value` type
     #[repr(transparent)]
     #[derive(Clone, Copy, PartialEq)]
-    pub struct Array3<T>(Value, PhantomData<T>);
+    pub struct Array3<T, L = CLayout>(Value, PhantomData<(T, L)>);
 
-    impl<T: Copy + Kind> Array3<T> {
+    impl<T: Copy + Kind, L: Layout> Array3<T, L> {
         /// Returns array view
         pub fn view(&self) -> ArrayView3<T> {
             let ba = self.0.custom_ptr_val::<bigarray::Bigarray>();
-            unsafe { ArrayView3::from_shape_ptr(self.shape(), (*ba).data as *const T) }
+            let shape = self.shape().set_f(is_fortran::<L>());
+            unsafe { ArrayView3::from_shape_ptr(shape, (*ba).data as *const T) }
         }
 
         /// Returns mutable array view
         pub fn view_mut(&mut self) -> ArrayViewMut3<T> {
             let ba = self.0.custom_ptr_val::<bigarray::Bigarray>();
-            unsafe { ArrayViewMut3::from_shape_ptr(self.shape(), (*ba).data as *mut T) }
+            let shape = self.shape().set_f(is_fortran::<L>());
+            unsafe { ArrayViewMut3::from_shape_ptr(shape, (*ba).data as *mut T) }
         }
 
         /// Returns the shape of `self`
@@ -656,26 +1376,34 @@ pub(crate) mod bigarray_ext {
         }
     }
 
-    unsafe impl<T> FromValue for Array3<T> {
-        fn from_value(value: Value) -> Array3<T> {
+    unsafe impl<T: Kind, L: Layout> FromValue for Array3<T, L> {
+        fn from_value(value: Value) -> Array3<T, L> {
+            expect_bigarray::<T, L>(value, Some(3));
             Array3(value, PhantomData)
         }
     }
 
-    unsafe impl<T> ToValue for Array3<T> {
+    unsafe impl<T: Kind, L: Layout> TryFromValue for Array3<T, L> {
+        fn try_from_value(value: Value) -> Result<Array3<T, L>, Error> {
+            check_bigarray::<T, L>(value, Some(3))?;
+            Ok(Array3(value, PhantomData))
+        }
+    }
+
+    unsafe impl<T, L> ToValue for Array3<T, L> {
         fn to_value(self) -> Value {
             self.0
         }
     }
 
-    impl<T: Copy + Kind> Array3<T> {
+    impl<T: Copy + Kind, L: Layout> Array3<T, L> {
         /// Create a new OCaml `Bigarray.Array3` with the given type and shape
-        pub fn create(dim: ndarray::Ix3) -> Array3<T> {
+        pub fn create(dim: ndarray::Ix3) -> Array3<T, L> {
             let x = crate::frame!((x) {
                 let data = unsafe { bigarray::malloc(dim.size() * mem::size_of::<T>()) };
                 x = unsafe {
                     Value(bigarray::caml_ba_alloc_dims(
-                        T::kind() | bigarray::Managed::MANAGED as i32,
+                        T::kind() | L::layout() | bigarray::Managed::MANAGED as i32,
                         3,
                         data,
                         dim[0] as sys::Intnat,
@@ -689,15 +1417,219 @@ pub(crate) mod bigarray_ext {
         }
     }
 
-    impl<T: Copy + Kind> From<ndarray::Array3<T>> for Array3<T> {
-        fn from(data: ndarray::Array3<T>) -> Array3<T> {
-            let dim = data.raw_dim();
-            let array = Array3::create(dim);
-            let ba = array.0.custom_ptr_val::<bigarray::Bigarray>();
+    impl<T: Copy + Kind, L: Layout> From<ndarray::Array3<T>> for Array3<T, L> {
+        fn from(data: ndarray::Array3<T>) -> Array3<T, L> {
+            let mut array = Array3::create(data.raw_dim());
+            array.view_mut().assign(&data);
+            array
+        }
+    }
+
+    /// OCaml Bigarray.Genarray type, with any number of dimensions. This introduces no
+    /// additional overhead compared to a `Value` type
+    #[repr(transparent)]
+    #[derive(Clone, Copy, PartialEq)]
+    pub struct Genarray<T, L = CLayout>(Value, PhantomData<(T, L)>);
+
+    impl<T: Copy + Kind, L: Layout> Genarray<T, L> {
+        /// Returns array view
+        pub fn view(&self) -> ArrayViewD<'_, T> {
+            let ba = self.0.custom_ptr_val::<bigarray::Bigarray>();
+            let shape = IxDyn(self.shape()).set_f(is_fortran::<L>());
+            unsafe { ArrayViewD::from_shape_ptr(shape, (*ba).data as *const T) }
+        }
+
+        /// Returns mutable array view
+        pub fn view_mut(&mut self) -> ArrayViewMutD<'_, T> {
+            let ba = self.0.custom_ptr_val::<bigarray::Bigarray>();
+            let shape = IxDyn(self.shape()).set_f(is_fortran::<L>());
+            unsafe { ArrayViewMutD::from_shape_ptr(shape, (*ba).data as *mut T) }
+        }
+
+        /// Returns the number of dimensions
+        pub fn ndim(&self) -> usize {
+            let ba = self.0.custom_ptr_val::<bigarray::Bigarray>();
+            unsafe { (*ba).num_dims as usize }
+        }
+
+        /// Returns the shape of `self`
+        pub fn shape(&self) -> &[usize] {
+            let ba = self.0.custom_ptr_val::<bigarray::Bigarray>();
+            unsafe { slice::from_raw_parts((*ba).dim.as_ptr() as *const usize, self.ndim()) }
+        }
+
+        /// Returns the number of items in `self`
+        pub fn len(&self) -> usize {
+            self.shape().iter().product()
+        }
+
+        /// Returns true when the array is empty
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+
+        /// Create a new OCaml `Bigarray.Genarray` with the given type and shape, returns an error
+        /// when `shape` has more than 16 dimensions
+        pub fn create(shape: &[usize]) -> Result<Genarray<T, L>, Error> {
+            check_rank(shape.len())?;
+            let dims: Vec<sys::Intnat> = shape.iter().map(|x| *x as sys::Intnat).collect();
+            let size = shape.iter().product::<usize>();
+            let x = crate::frame!((x) {
+                let data = unsafe { bigarray::malloc(size * mem::size_of::<T>()) };
+                x = unsafe {
+                    Value(bigarray::caml_ba_alloc(
+                        T::kind() | L::layout() | bigarray::Managed::MANAGED as i32,
+                        dims.len() as i32,
+                        data,
+                        dims.as_ptr(),
+                    ))
+                };
+                x
+            });
+            Ok(Genarray(x, PhantomData))
+        }
+    }
+
+    impl<T: Copy + Kind, L: Layout> Genarray<T, L> {
+        /// Copy the contents of `self` into `dst`, like `Bigarray.Genarray.blit`. The arrays may
+        /// overlap, an error is returned when they have different shapes
+        pub fn blit(&self, dst: &mut Genarray<T, L>) -> Result<(), Error> {
+            if self.shape() != dst.shape() {
+                return Err(
+                    CamlError::InvalidArgument("Bigarray.blit: dimension mismatch".into()).into(),
+                );
+            }
             unsafe {
-                ptr::copy_nonoverlapping(data.as_ptr(), (*ba).data as *mut T, dim.size());
+                bigarray::caml_ba_blit((self.0).0, (dst.0).0);
             }
-            array
+            Ok(())
+        }
+
+        /// Copy `src` into `self` in memory order, returns an error when `src` has a different
+        /// number of items
+        pub fn blit_from_slice(&mut self, src: &[T]) -> Result<(), Error> {
+            if src.len() != self.len() {
+                return Err(CamlError::InvalidArgument(
+                    "Bigarray.blit_from_slice: dimension mismatch".into(),
+                )
+                .into());
+            }
+            // `src` may point into the same data if it was borrowed from a sub-array
+            let ba = self.0.custom_ptr_val::<bigarray::Bigarray>();
+            unsafe { ptr::copy(src.as_ptr(), (*ba).data as *mut T, src.len()) }
+            Ok(())
+        }
+
+        /// Copy the contents of `self` into `dst` in memory order, returns an error when `dst`
+        /// has a different number of items
+        pub fn copy_to_slice(&self, dst: &mut [T]) -> Result<(), Error> {
+            if dst.len() != self.len() {
+                return Err(CamlError::InvalidArgument(
+                    "Bigarray.copy_to_slice: dimension mismatch".into(),
+                )
+                .into());
+            }
+            let ba = self.0.custom_ptr_val::<bigarray::Bigarray>();
+            unsafe { ptr::copy((*ba).data as *const T, dst.as_mut_ptr(), dst.len()) }
+            Ok(())
+        }
+    }
+
+    impl<T: Copy + Kind> Genarray<T, CLayout> {
+        /// Returns the sub-array of `self` at `index`, like `Bigarray.Genarray.slice_left`. The
+        /// result has `self.ndim() - index.len()` dimensions and shares its data with `self`, the
+        /// data stays alive until both arrays have been garbage collected
+        pub fn slice_left(&self, index: &[usize]) -> Result<Genarray<T, CLayout>, Error> {
+            if index.len() > self.ndim() {
+                return Err(
+                    CamlError::InvalidArgument("Bigarray.slice: too many indices".into()).into(),
+                );
+            }
+            if index.iter().zip(self.shape()).any(|(i, n)| i >= n) {
+                return Err(CamlError::InvalidArgument("Bigarray.slice: bad index".into()).into());
+            }
+
+            // The parent is rooted before allocating the indices, since it could be moved by the GC
+            let x = crate::frame!((x, indices, parent) {
+                parent = self.0;
+                indices = Value::alloc(index.len(), crate::Tag(0));
+                for (n, i) in index.iter().enumerate() {
+                    indices.store_field(n, Value::int(*i as crate::Int));
+                }
+                x = unsafe { Value(bigarray::caml_ba_slice(parent.0, indices.0)) };
+                x
+            });
+            Ok(Genarray(x, PhantomData))
+        }
+    }
+
+    fn check_rank(rank: usize) -> Result<(), Error> {
+        if rank > MAX_DIMS {
+            return Err(ConversionError::InvalidRank {
+                expected: MAX_DIMS,
+                found: rank,
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    unsafe impl<T: Kind, L: Layout> FromValue for Genarray<T, L> {
+        fn from_value(value: Value) -> Genarray<T, L> {
+            expect_bigarray::<T, L>(value, None);
+            Genarray(value, PhantomData)
+        }
+    }
+
+    unsafe impl<T: Kind, L: Layout> TryFromValue for Genarray<T, L> {
+        fn try_from_value(value: Value) -> Result<Genarray<T, L>, Error> {
+            check_bigarray::<T, L>(value, None)?;
+            Ok(Genarray(value, PhantomData))
+        }
+    }
+
+    unsafe impl<T, L> ToValue for Genarray<T, L> {
+        fn to_value(self) -> Value {
+            self.0
+        }
+    }
+
+    /// Moves the data into the bigarray without copying when `data` is in standard layout (or
+    /// column-major order for `FortranLayout`), returns an error when `data` has more than 16
+    /// dimensions
+    impl<T: Copy + Kind, L: Layout> core::convert::TryFrom<ndarray::ArrayD<T>> for Genarray<T, L> {
+        type Error = Error;
+
+        fn try_from(data: ndarray::ArrayD<T>) -> Result<Genarray<T, L>, Error> {
+            check_rank(data.ndim())?;
+            let dims: Vec<sys::Intnat> = data.shape().iter().map(|x| *x as sys::Intnat).collect();
+            let len = data.len();
+            // Fortran arrays are stored in the same order as the transposed C array
+            let data = if is_fortran::<L>() {
+                data.reversed_axes()
+            } else {
+                data
+            };
+            let items = if len == 0 {
+                Vec::new()
+            } else if data.is_standard_layout() {
+                // The array may only use part of its buffer
+                let start = data.as_ptr();
+                let mut items = data.into_raw_vec();
+                let offset = (start as usize - items.as_ptr() as usize)
+                    .checked_div(mem::size_of::<T>())
+                    .unwrap_or(0);
+                if offset != 0 || items.len() != len {
+                    items = items[offset..offset + len].to_vec();
+                }
+                items
+            } else {
+                data.iter().copied().collect()
+            };
+            Ok(Genarray(
+                alloc_owned::<T, L>(items.into_boxed_slice(), &dims),
+                PhantomData,
+            ))
         }
     }
 }
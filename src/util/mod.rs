@@ -3,3 +3,39 @@ pub use cstr_core::CString;
 
 #[cfg(not(feature = "no-std"))]
 pub use std::ffi::CString;
+
+use crate::{sys, Value};
+
+/// Formats `args` directly into a newly allocated OCaml string. Nothing is allocated on the Rust
+/// side, so the result can be passed to functions that raise without leaking memory.
+pub fn format_value(args: core::fmt::Arguments) -> Value {
+    struct Count(usize);
+
+    impl core::fmt::Write for Count {
+        fn write_str(&mut self, s: &str) -> core::fmt::Result {
+            self.0 += s.len();
+            Ok(())
+        }
+    }
+
+    struct Fill<'a>(&'a mut [u8], usize);
+
+    impl<'a> core::fmt::Write for Fill<'a> {
+        fn write_str(&mut self, s: &str) -> core::fmt::Result {
+            let n = s.len().min(self.0.len() - self.1);
+            self.0[self.1..self.1 + n].copy_from_slice(&s.as_bytes()[..n]);
+            self.1 += n;
+            Ok(())
+        }
+    }
+
+    let mut count = Count(0);
+    let _ = core::fmt::write(&mut count, args);
+
+    unsafe {
+        let value = sys::caml_alloc_string(count.0);
+        let data = core::slice::from_raw_parts_mut(sys::string_val(value), count.0);
+        let _ = core::fmt::write(&mut Fill(data, 0), args);
+        Value(value)
+    }
+}
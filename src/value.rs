@@ -29,6 +29,21 @@ pub unsafe trait FromValue {
     fn from_value(v: Value) -> Self;
 }
 
+/// `TryFromValue` is used to convert from OCaml values to Rust types, checking that the value
+/// has the expected layout before converting it.
+///
+/// Unlike `FromValue`, which assumes the value has the correct type, `TryFromValue` inspects
+/// the value (immediate or block, tag, size, ...) and returns `Error::Conversion` when it does
+/// not match. This is what `ocaml::func` uses to convert function arguments.
+///
+/// # Safety
+///
+/// Implementations must not return `Ok` for values that cannot be safely read as `Self`
+pub unsafe trait TryFromValue: Sized {
+    /// Convert from OCaml value, returning an error if the value has an unexpected layout
+    fn try_from_value(v: Value) -> Result<Self, Error>;
+}
+
 unsafe impl ToValue for Value {
     fn to_value(self) -> Value {
         Value(self.0)
@@ -42,6 +57,13 @@ unsafe impl FromValue for Value {
     }
 }
 
+unsafe impl TryFromValue for Value {
+    #[inline]
+    fn try_from_value(v: Value) -> Result<Value, Error> {
+        Ok(v)
+    }
+}
+
 const NONE: Value = Value(sys::NONE);
 const UNIT: Value = Value(sys::UNIT);
 
@@ -109,10 +131,24 @@ impl Value {
 
     /// Allocate custom value
     pub fn alloc_custom<T: crate::Custom>() -> Value {
-        let size = core::mem::size_of::<T>();
+        let size = crate::custom::custom_size::<T>();
         crate::frame!((x) {
             unsafe {
                 x = Value(sys::caml_alloc_custom(T::ops() as *const _ as *const sys::custom_operations, size, T::USED, T::MAX));
+                crate::custom::init_borrow_flag::<T>(x.custom_ptr_val_mut());
+                x
+            }
+        })
+    }
+
+    /// Allocate custom value that owns `mem` bytes outside of the OCaml heap, this uses
+    /// `caml_alloc_custom_mem` instead of the `USED` and `MAX` values from `Custom`
+    pub fn alloc_custom_mem<T: crate::Custom>(mem: usize) -> Value {
+        let size = crate::custom::custom_size::<T>();
+        crate::frame!((x) {
+            unsafe {
+                x = Value(sys::caml_alloc_custom_mem(T::ops() as *const _ as *mut sys::custom_operations, size, mem));
+                crate::custom::init_borrow_flag::<T>(x.custom_ptr_val_mut());
                 x
             }
         })
@@ -143,7 +179,8 @@ impl Value {
         unsafe { sys::caml_array_length(self.0) }
     }
 
-    /// See caml_register_global_root
+    /// See caml_register_global_root, `Root` should be preferred since it removes the root
+    /// automatically
     pub fn register_global_root(&mut self) {
         crate::frame!((x) {
             x = Value(self.0);
@@ -161,6 +198,11 @@ impl Value {
         unsafe { sys::tag_val(self.0).into() }
     }
 
+    /// Get the number of fields in the underlying OCaml block
+    pub fn size(self) -> Size {
+        unsafe { sys::wosize_val(self.0) }
+    }
+
     /// Convert a boolean to OCaml value
     pub const fn bool(b: bool) -> Value {
         Value::int(b as crate::Int)
@@ -371,7 +413,7 @@ impl Value {
 
         if v.is_exception_result() {
             v = v.exception().unwrap();
-            Err(CamlError::Exception(v).into())
+            Err(CamlError::from_exception(v).into())
         } else {
             Ok(v)
         }
@@ -392,7 +434,7 @@ impl Value {
 
         if v.is_exception_result() {
             v = v.exception().unwrap();
-            Err(CamlError::Exception(v).into())
+            Err(CamlError::from_exception(v).into())
         } else {
             Ok(v)
         }
@@ -423,7 +465,7 @@ impl Value {
 
         if v.is_exception_result() {
             v = v.exception().unwrap();
-            Err(CamlError::Exception(v).into())
+            Err(CamlError::from_exception(v).into())
         } else {
             Ok(v)
         }
@@ -451,7 +493,7 @@ impl Value {
 
         if v.is_exception_result() {
             v = v.exception().unwrap();
-            Err(CamlError::Exception(v).into())
+            Err(CamlError::from_exception(v).into())
         } else {
             Ok(v)
         }
@@ -462,6 +504,35 @@ impl Value {
         unsafe { sys::caml_modify(&mut self.0, v.to_value().0) }
     }
 
+    /// Compare two values using OCaml's polymorphic `compare`
+    ///
+    /// Like `compare`, this raises `Invalid_argument` when comparing functional values
+    pub fn compare(self, other: Value) -> core::cmp::Ordering {
+        let x = unsafe { Value(sys::caml_compare(self.0, other.0)) };
+        x.int_val().cmp(&0)
+    }
+
+    /// Check if two values are structurally equal using OCaml's polymorphic `=`, unlike
+    /// `Value::compare`, `nan` is not equal to itself
+    ///
+    /// Like `=`, this raises `Invalid_argument` when comparing functional values
+    pub fn equal(self, other: Value) -> bool {
+        unsafe { Value(sys::caml_equal(self.0, other.0)).int_val() != 0 }
+    }
+
+    /// Hash a value, this is the same as `Hashtbl.hash`
+    pub fn hash(self) -> crate::Int {
+        let x = unsafe {
+            Value(sys::caml_hash(
+                Value::int(10).0,
+                Value::int(100).0,
+                Value::int(0).0,
+                self.0,
+            ))
+        };
+        x.int_val()
+    }
+
     /// Determines if the current value is an exception
     pub fn is_exception_result(self) -> bool {
         crate::sys::is_exception_result(self.0)
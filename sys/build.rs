@@ -132,6 +132,13 @@ fn run() -> std::io::Result<()> {
         println!("cargo:rustc-cfg=caml_state");
     }
 
+    println!("cargo:rustc-check-cfg=cfg(caml_ba_sub_ops)");
+    if (major, minor) >= (4, 10) {
+        // `caml_ba_sub`, `caml_ba_slice` and `caml_ba_reshape` copy the custom operations of the
+        // parent array to the sub-array
+        println!("cargo:rustc-cfg=caml_ba_sub_ops");
+    }
+
     #[cfg(feature = "link")]
     link(out_dir, bin_path, ocaml_path.as_ref())?;
 
@@ -7,9 +7,9 @@ pub type Data = *mut c_void;
 
 #[repr(C)]
 pub struct BigarrayProxy {
-    refcount: Intnat,
-    data: Data,
-    size: Uintnat,
+    pub refcount: Intnat,
+    pub data: Data,
+    pub size: Uintnat,
 }
 
 #[repr(C)]
@@ -29,6 +29,15 @@ pub enum Managed {
     MANAGED_MASK = 0x600, /* Mask for "managed" bits in flags field */
 }
 
+#[allow(non_camel_case_types)]
+pub enum Layout {
+    C_LAYOUT = 0,           /* Row major, indices start at 0 */
+    FORTRAN_LAYOUT = 0x100, /* Column major, indices start at 1 */
+}
+
+/* Mask for layout in flags field */
+pub const LAYOUT_MASK: i32 = 0x100;
+
 #[allow(non_camel_case_types)]
 pub enum Kind {
     FLOAT32 = 0x00,    /* Single-precision floats */
@@ -47,9 +56,19 @@ pub enum Kind {
     KIND_MASK = 0xFF,  /* Mask for kind in flags field */
 }
 
+/// True when sub-arrays created using `caml_ba_sub`, `caml_ba_slice` and `caml_ba_reshape` copy the
+/// custom operations of their parent (OCaml 4.10 and later), otherwise they always use
+/// `caml_ba_ops`
+pub const SUB_INHERITS_OPS: bool = cfg!(caml_ba_sub_ops);
+
 extern "C" {
     pub fn malloc(size: usize) -> Data;
-    pub fn caml_ba_alloc(flags: i32, num_dims: i32, data: Data, dim: *const i32) -> Value;
+    pub fn free(data: Data);
+    pub static caml_ba_ops: crate::custom_operations;
+    pub fn caml_ba_alloc(flags: i32, num_dims: i32, data: Data, dim: *const Intnat) -> Value;
     pub fn caml_ba_alloc_dims(flags: i32, num_dims: i32, data: Data, ...) -> Value;
     pub fn caml_ba_byte_size(b: *const Bigarray) -> u32;
+    pub fn caml_ba_sub(vb: Value, vofs: Value, vlen: Value) -> Value;
+    pub fn caml_ba_slice(vb: Value, vind: Value) -> Value;
+    pub fn caml_ba_blit(vsrc: Value, vdst: Value) -> Value;
 }
@@ -18,4 +18,6 @@ extern "C" {
     pub fn caml_raise_not_found();
     pub fn caml_array_bound_error();
     pub fn caml_raise_sys_blocked_io();
+    pub fn caml_get_exception_raw_backtrace(unit: Value) -> Value;
+    pub fn caml_restore_raw_backtrace(exn: Value, backtrace: Value) -> Value;
 }
@@ -69,6 +69,7 @@ pub mod bigarray;
 mod callback;
 mod custom;
 mod fail;
+mod marshal;
 mod runtime;
 mod state;
 mod tag;
@@ -79,6 +80,7 @@ pub use alloc::*;
 pub use callback::*;
 pub use custom::*;
 pub use fail::*;
+pub use marshal::*;
 pub use memory::*;
 pub use mlvalues::*;
 pub use runtime::*;
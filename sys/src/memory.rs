@@ -35,7 +35,7 @@
 use core::default::Default;
 use core::ptr;
 
-use crate::mlvalues::{field, Size, Value};
+use crate::mlvalues::{field, Size, Uintnat, Value};
 
 #[repr(C)]
 #[derive(Debug, Clone)]
@@ -105,4 +105,13 @@ extern "C" {
     pub fn caml_leave_blocking_section();
     pub fn caml_register_global_root(value: *mut Value);
     pub fn caml_remove_global_root(value: *mut Value);
+    pub fn caml_register_generational_global_root(value: *mut Value);
+    pub fn caml_remove_generational_global_root(value: *mut Value);
+    pub fn caml_modify_generational_global_root(value: *mut Value, newval: Value);
+    pub fn caml_minor_collection();
+    pub fn caml_adjust_gc_speed(res: Uintnat, max: Uintnat);
+    pub fn caml_alloc_dependent_memory(bsz: Uintnat);
+    pub fn caml_free_dependent_memory(bsz: Uintnat);
+    pub fn caml_gc_get(unit: Value) -> Value;
+    pub fn caml_gc_quick_stat(unit: Value) -> Value;
 }
@@ -140,4 +140,7 @@ extern "C" {
     pub fn caml_array_length(value: Value) -> Size;
     pub fn caml_hash_variant(tag: *const u8) -> Value;
     pub fn caml_get_public_method(obj: Value, tag: Value) -> Value;
+    pub fn caml_compare(v1: Value, v2: Value) -> Value;
+    pub fn caml_equal(v1: Value, v2: Value) -> Value;
+    pub fn caml_hash(count: Value, limit: Value, seed: Value, obj: Value) -> Value;
 }
@@ -26,3 +26,87 @@ external apply_range: (int list -> 'a) -> int -> int -> 'a = "apply_range"
 
 let%test "apply range 1" =
   Util.check_leaks (fun () -> apply_range (List.map (fun a  -> let () = Util.gc () in a + 1)) 0 10 = [1; 2; 3; 4; 5; 6; 7; 8; 9; 10])
+
+external exception_to_string: (unit -> 'a) -> string = "exception_to_string"
+external exception_is_not_found: (unit -> 'a) -> bool = "exception_is_not_found"
+external exception_args: (unit -> 'a) -> int * int = "exception_args"
+
+exception Custom of int * string
+
+let%test "exception to string" = Util.check_leaks (fun () ->
+  exception_to_string (fun () -> failwith "abc") = "Failure(\"abc\")" &&
+  exception_to_string (fun () -> raise Not_found) = "Not_found" &&
+  exception_is_not_found (fun () -> raise Not_found) &&
+  not (exception_is_not_found (fun () -> raise Exit)))
+
+let%test "exception args" = Util.check_leaks (fun () ->
+  exception_args (fun () -> raise (Custom (1, "a"))) = (2, 1))
+
+let%test "exception printexc" = Util.check_leaks (fun () ->
+  let () = Callback.register "Printexc.to_string" Printexc.to_string in
+  exception_to_string (fun () -> raise (Custom (1, "a"))) = Printexc.to_string (Custom (1, "a")))
+
+external exception_has_backtrace: (unit -> 'a) -> bool = "exception_has_backtrace"
+
+let with_backtrace f =
+  Printexc.record_backtrace true;
+  let x = f () in
+  Printexc.record_backtrace false;
+  x
+
+let%test "exception backtrace" = Util.check_leaks (fun () ->
+  with_backtrace (fun () -> exception_has_backtrace (fun () -> raise Exit)))
+
+let%test "exception reraise backtrace" = Util.check_leaks (fun () ->
+  with_backtrace (fun () ->
+    try apply1 (fun () -> raise Exit) (); false
+    with Exit -> Printexc.raw_backtrace_length (Printexc.get_raw_backtrace ()) > 0))
+
+let%test "builtin exception backtrace" = Util.check_leaks (fun () ->
+  with_backtrace (fun () ->
+    exception_has_backtrace (fun () -> failwith "abc") &&
+    exception_is_not_found (fun () -> raise Not_found) &&
+    try apply1 (fun () -> failwith "abc") (); false
+    with Failure _ -> Printexc.raw_backtrace_length (Printexc.get_raw_backtrace ()) > 0))
+
+external apply_typed: (int -> string -> string) -> int -> string = "apply_typed"
+external apply_partial: (int -> (int -> int)) -> int = "apply_partial"
+
+let%test "typed closure" = Util.check_leaks (fun () ->
+  apply_typed (fun i s -> Util.gc (); string_of_int i ^ s) 1 = "1abc" &&
+  apply_typed (fun i -> let x = string_of_int i in fun s -> x ^ s) 2 = "2abc")
+
+let%test "typed closure partial application" = Util.check_leaks (fun () ->
+  apply_partial (fun a b -> a + b) = 3 &&
+  apply_partial (fun a -> fun b -> a * 10 + b) = 12)
+
+let%test "typed closure exception" = Util.check_leaks (fun () ->
+  try let _ = apply_typed (fun _ _ -> raise Not_found) 1 in false
+  with Not_found -> true)
+
+external closure_call : 'state -> 'a -> 'b = "ocaml_rs_closure_call"
+let () = Callback.register "ocaml_rs_closure" (fun state -> closure_call state)
+
+external make_counter: int -> (int -> int) = "make_counter"
+external closures_dropped: unit -> int = "closures_dropped"
+
+let%test "rust closure" = Util.check_leaks (fun () ->
+  let f = make_counter 10 in
+  List.iter (fun x -> ignore (f x)) [1; 2; 3];
+  Util.gc ();
+  f 0 = 16)
+
+let%test "rust closure finalizer" =
+  let dropped = closures_dropped () in
+  let () = ignore (make_counter 0 1) in
+  Gc.full_major ();
+  closures_dropped () > dropped
+
+external make_caller: unit -> ((unit -> unit) -> unit) = "make_caller"
+
+let%test "rust closure reentrant" = Util.check_leaks (fun () ->
+  let f = make_caller () in
+  f (fun () -> ());
+  (try f (fun () -> f (fun () -> ())); false
+   with Failure msg -> msg = "closure called again before returning") &&
+  (f (fun () -> ()); true))
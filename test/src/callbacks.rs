@@ -19,3 +19,86 @@ pub fn apply_range(f: Value, start: ocaml::Int, stop: ocaml::Int) -> Result<Valu
 
     f.call(l)
 }
+
+#[ocaml::func]
+pub fn exception_to_string(f: Value) -> String {
+    match f.call(Value::unit()) {
+        Ok(_) => String::new(),
+        Err(Error::Caml(e)) => e.to_string(),
+        Err(_) => String::from("error"),
+    }
+}
+
+#[ocaml::func]
+pub fn exception_is_not_found(f: Value) -> bool {
+    matches!(
+        f.call(Value::unit()),
+        Err(Error::Caml(ocaml::CamlError::NotFound))
+    )
+}
+
+#[ocaml::func]
+pub fn exception_args(f: Value) -> (ocaml::Int, ocaml::Int) {
+    match f.call(Value::unit()) {
+        Err(Error::Caml(ocaml::CamlError::Exception(e, _))) => {
+            let args = ocaml::CamlError::exception_args(e);
+            (args.len() as ocaml::Int, args[0].int_val())
+        }
+        _ => (0, 0),
+    }
+}
+
+#[ocaml::func]
+pub fn exception_has_backtrace(f: Value) -> bool {
+    match f.call(Value::unit()) {
+        Err(Error::Caml(e)) => e.backtrace().is_some(),
+        _ => false,
+    }
+}
+
+#[ocaml::func]
+pub fn apply_typed(
+    f: ocaml::Fn2<ocaml::Int, String, String>,
+    i: ocaml::Int,
+) -> Result<String, Error> {
+    f.call(i, String::from("abc"))
+}
+
+#[ocaml::func]
+pub fn apply_partial(
+    f: ocaml::Fn1<ocaml::Int, ocaml::Fn1<ocaml::Int, ocaml::Int>>,
+) -> Result<ocaml::Int, Error> {
+    let g = f.call(1)?;
+    g.call(2)
+}
+
+static CLOSURES_DROPPED: std::sync::atomic::AtomicIsize = std::sync::atomic::AtomicIsize::new(0);
+
+struct DropCounter;
+
+impl Drop for DropCounter {
+    fn drop(&mut self) {
+        CLOSURES_DROPPED.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+    }
+}
+
+#[ocaml::func]
+pub fn make_counter(start: ocaml::Int) -> Result<ocaml::Fn1<ocaml::Int, ocaml::Int>, Error> {
+    let mut total = start;
+    let counter = DropCounter;
+    ocaml::Fn1::new(move |x: ocaml::Int| {
+        let _ = &counter;
+        total += x;
+        total
+    })
+}
+
+#[ocaml::func]
+pub fn closures_dropped() -> ocaml::Int {
+    CLOSURES_DROPPED.load(std::sync::atomic::Ordering::SeqCst) as ocaml::Int
+}
+
+#[ocaml::func]
+pub fn make_caller() -> Result<ocaml::Fn1<Value, Result<(), Error>>, Error> {
+    ocaml::Fn1::new(|f: Value| f.call(Value::unit()).map(|_| ()))
+}
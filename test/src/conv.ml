@@ -110,3 +110,24 @@ external get_pair_vec: unit -> (string * int) array = "pair_vec"
 let%test "get-pair-vec" = Util.check_leaks (fun () -> (
   get_pair_vec () = [| "foo", 1; "bar", 2 |]
 ))
+
+external enum1_first_unchecked: string -> enum1 = "enum1_first"
+external enum1_is_empty_unchecked: int -> bool = "enum1_is_empty"
+
+let%test "enum1 first (invalid argument)" = Util.check_leaks (fun () -> (
+  try
+    let _ = enum1_first_unchecked "testing" in
+    false
+  with Invalid_argument _ -> true
+))
+
+let%test "enum1 is empty (invalid argument)" = Util.check_leaks (fun () -> (
+  try ignore (enum1_is_empty_unchecked 10); false
+  with Invalid_argument _ -> true
+))
+
+external u32_incr: int32 -> int32 = "u32_incr"
+
+let%test "u32 int32" = Util.check_leaks (fun () -> (
+  u32_incr 1l = 2l && u32_incr Int32.max_int = Int32.min_int && u32_incr (-1l) = 0l
+))
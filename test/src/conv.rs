@@ -100,3 +100,8 @@ pub unsafe fn deep_clone(a: ocaml::Value) -> ocaml::Value {
 pub fn pair_vec() -> ocaml::Value {
     vec![("foo", 1), ("bar", 2isize)].to_value()
 }
+
+#[ocaml::func]
+pub fn u32_incr(x: u32) -> u32 {
+    x.wrapping_add(1)
+}
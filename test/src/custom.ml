@@ -4,6 +4,7 @@ external testing_alloc: int64 -> testing = "testing_alloc"
 external testing_set_c: testing -> string -> unit = "testing_set_c"
 external testing_set_a: testing -> float -> unit = "testing_set_a"
 external testing_get_values: testing -> (float * int64 * string) = "testing_get_values"
+external testing_try_get_values: testing -> (float * int64 * string) = "testing_try_get_values"
 
 let%test "testing compare 1" = Util.check_leaks (fun () -> testing_alloc 0L <> testing_alloc 1L)
 let%test "testing compare 2" = Util.check_leaks (fun () -> testing_alloc 99L = testing_alloc 99L)
@@ -36,3 +37,88 @@ let%test "testing callback 2" = Util.check_leaks (fun () -> (
   Util.gc ();
   testing_callback_call c 5 = sin 5.0)
 )
+
+type serialized
+external serialized_register: unit -> unit = "serialized_register"
+external serialized_alloc: string -> int64 array -> serialized = "serialized_alloc"
+external serialized_get: serialized -> string * int64 array = "serialized_get"
+
+let () = serialized_register ()
+
+let%test "serialized marshal" = Util.check_leaks (fun () -> (
+  let t = serialized_alloc "testing" [| 1L; 2L; 3L |] in
+  let s = Marshal.to_string t [] in
+  Util.gc ();
+  let t' : serialized = Marshal.from_string s 0 in
+  Util.gc ();
+  serialized_get t' = ("testing", [| 1L; 2L; 3L |])
+))
+
+let%test "unserialized marshal" = Util.check_leaks (fun () -> (
+  let t = testing_alloc 1L in
+  try ignore (Marshal.to_string t []); false with Invalid_argument _ -> true
+))
+
+type version
+external version_alloc: int -> int -> string -> version = "version_alloc"
+external version_name: version -> string = "version_name"
+
+let%test "custom derive compare" = Util.check_leaks (fun () -> (
+  let a = version_alloc 1 2 "a" in
+  let b = version_alloc 1 10 "b" in
+  Util.gc ();
+  compare a b < 0 && compare b a > 0 && a = version_alloc 1 2 "a" && a <> version_alloc 1 2 "c"
+))
+
+let%test "custom derive compare_ext" = Util.check_leaks (fun () -> (
+  let a = version_alloc 1 2 "a" in
+  compare (Obj.repr a) (Obj.repr 1) > 0 && compare (Obj.repr 2) (Obj.repr a) > 0
+))
+
+let%test "custom derive hash" = Util.check_leaks (fun () -> (
+  let a = version_alloc 1 2 "a" in
+  Util.gc ();
+  Hashtbl.hash a = Hashtbl.hash (version_alloc 1 2 "a") && version_name a = "a"
+))
+
+let%test "pointer type mismatch" = Util.check_leaks (fun () -> (
+  let v : testing = Obj.magic (version_alloc 1 2 "a") in
+  try ignore (testing_try_get_values v); false with Invalid_argument _ -> true
+))
+
+type buffer
+external buffer_alloc: int -> buffer = "buffer_alloc"
+external buffer_grow: buffer -> int -> unit = "buffer_grow"
+external buffer_length: buffer -> int = "buffer_length"
+
+let%test "custom mem" = Util.check_leaks (fun () -> (
+  let b = buffer_alloc 1024 in
+  Util.gc ();
+  buffer_grow b (1024 * 1024);
+  Util.gc ();
+  buffer_length b = 1024 * 1024
+))
+
+external buffer_copy: buffer -> buffer -> unit = "buffer_copy"
+
+let%test "pointer borrow" = Util.check_leaks (fun () -> (
+  let a = buffer_alloc 10 in
+  let b = buffer_alloc 20 in
+  buffer_copy a b;
+  Util.gc ();
+  buffer_length a = 20
+))
+
+let%test "pointer borrow conflict" = Util.check_leaks (fun () -> (
+  let a = buffer_alloc 10 in
+  let raised = try buffer_copy a a; false with Failure _ -> true in
+  (* The borrows are released after the error *)
+  buffer_copy a (buffer_alloc 5);
+  raised && buffer_length a = 5
+))
+
+external version_borrow_name: version -> string = "version_borrow_name"
+
+let%test "pointer borrow not enabled" = Util.check_leaks (fun () -> (
+  try ignore (version_borrow_name (version_alloc 1 2 "a")); false with Failure _ -> true
+))
@@ -1,4 +1,4 @@
-use ocaml::{FromValue, Value};
+use ocaml::{Custom, FromValue, Value};
 
 struct Testing {
     a: ocaml::Float,
@@ -51,22 +51,29 @@ pub fn testing_get_values(testing: ocaml::Pointer<Testing>) -> (ocaml::Float, i6
     (t.a, t.b, t.c.clone())
 }
 
+#[ocaml::func]
+pub fn testing_try_get_values(testing: Value) -> Result<(ocaml::Float, i64, String), ocaml::Error> {
+    let testing: ocaml::Pointer<Testing> = ocaml::TryFromValue::try_from_value(testing)?;
+    let t = testing.as_ref();
+    Ok((t.a, t.b, t.c.clone()))
+}
+
 struct TestingCallback {
-    func: ocaml::Value,
+    func: ocaml::Root,
 }
 
 unsafe extern "C" fn testing_callback_finalize(a: Value) {
-    let mut t0 = ocaml::Pointer::<TestingCallback>::from_value(a);
-    t0.as_mut().func.remove_global_root();
+    let t0 = ocaml::Pointer::<TestingCallback>::from_value(a);
     t0.drop_in_place();
 }
 
 ocaml::custom_finalize!(TestingCallback, testing_callback_finalize);
 
 #[ocaml::func]
-pub fn testing_callback_alloc(mut func: ocaml::Value) -> TestingCallback {
-    func.register_global_root();
-    TestingCallback { func }
+pub fn testing_callback_alloc(func: ocaml::Value) -> TestingCallback {
+    TestingCallback {
+        func: ocaml::Root::new(func),
+    }
 }
 
 #[ocaml::func]
@@ -74,5 +81,129 @@ pub fn testing_callback_call(
     t: ocaml::Pointer<TestingCallback>,
     x: ocaml::Value,
 ) -> Result<ocaml::Value, ocaml::Error> {
-    t.as_ref().func.call(x)
+    t.as_ref().func.get().call(x)
+}
+
+struct Serialized {
+    name: String,
+    values: Vec<i64>,
+}
+
+unsafe extern "C" fn serialized_finalize(a: Value) {
+    let t0 = ocaml::Pointer::<Serialized>::from_value(a);
+    t0.drop_in_place();
+}
+
+ocaml::custom_serialize!(Serialized {
+    finalize: serialized_finalize,
+});
+
+impl ocaml::custom::Serialize for Serialized {
+    fn serialize(&self, s: &mut ocaml::custom::Serializer) {
+        s.write_u32(self.name.len() as u32);
+        s.write_bytes(self.name.as_bytes());
+        s.write_u32(self.values.len() as u32);
+        for x in &self.values {
+            s.write_i64(*x);
+        }
+    }
+
+    fn deserialize(d: &mut ocaml::custom::Deserializer) -> Option<Self> {
+        let mut name = vec![0; d.read_u32() as usize];
+        d.read_bytes(&mut name);
+        let values = (0..d.read_u32()).map(|_| d.read_i64()).collect();
+        Some(Serialized {
+            name: String::from_utf8(name).ok()?,
+            values,
+        })
+    }
+}
+
+#[ocaml::func]
+pub fn serialized_register() {
+    ocaml::custom::register::<Serialized>()
+}
+
+#[ocaml::func]
+pub fn serialized_alloc(name: String, values: Vec<i64>) -> Serialized {
+    Serialized { name, values }
+}
+
+#[ocaml::func]
+pub fn serialized_get(t: ocaml::Pointer<Serialized>) -> (String, Vec<i64>) {
+    let t = t.as_ref();
+    (t.name.clone(), t.values.clone())
+}
+
+#[derive(ocaml::Custom, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[ocaml(compare, compare_ext, hash)]
+pub struct Version {
+    major: ocaml::Int,
+    minor: ocaml::Int,
+    name: String,
+}
+
+impl PartialEq<ocaml::Int> for Version {
+    fn eq(&self, other: &ocaml::Int) -> bool {
+        self.major == *other && self.minor == 0
+    }
+}
+
+impl PartialOrd<ocaml::Int> for Version {
+    fn partial_cmp(&self, other: &ocaml::Int) -> Option<std::cmp::Ordering> {
+        Some((self.major, self.minor).cmp(&(*other, 0)))
+    }
+}
+
+#[ocaml::func]
+pub fn version_alloc(major: ocaml::Int, minor: ocaml::Int, name: String) -> Version {
+    Version { major, minor, name }
+}
+
+#[ocaml::func]
+pub fn version_name(v: ocaml::Pointer<Version>) -> String {
+    v.as_ref().name.clone()
+}
+
+#[ocaml::func]
+pub fn version_borrow_name(v: ocaml::Pointer<Version>) -> Result<String, ocaml::Error> {
+    Ok(v.borrow()?.name.clone())
+}
+
+#[derive(ocaml::Custom)]
+#[ocaml(name = "rust.Buffer", borrow_checked, heap_size = "Buffer::capacity")]
+pub struct Buffer(Vec<u8>);
+
+impl Buffer {
+    fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+}
+
+#[ocaml::func]
+pub fn buffer_alloc(size: usize) -> Buffer {
+    Buffer(vec![0; size])
+}
+
+#[ocaml::func]
+pub fn buffer_grow(mut b: ocaml::Pointer<Buffer>, size: usize) {
+    let previous = b.as_ref().heap_size();
+    b.as_mut().0.resize(size, 0);
+    b.update_heap_size(previous);
+}
+
+#[ocaml::func]
+pub fn buffer_length(b: ocaml::Pointer<Buffer>) -> usize {
+    b.as_ref().0.len()
+}
+
+#[ocaml::func]
+pub fn buffer_copy(
+    dest: ocaml::Pointer<Buffer>,
+    src: ocaml::Pointer<Buffer>,
+) -> Result<(), ocaml::Error> {
+    let src = src.borrow()?;
+    let mut dest = dest.borrow_mut()?;
+    dest.0.clone_from(&src.0);
+    Ok(())
 }
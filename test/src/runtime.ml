@@ -33,6 +33,36 @@ let%test "raise failure" = try
   raise_failure ()
 with Failure e -> let () = Util.gc () in e = "An error"
 
+external raise_failure_owned: int -> bool = "raise_failure_owned"
+external raise_invalid_argument: string -> bool = "raise_invalid_argument"
+
+let%test "raise failure owned" = Util.check_leaks (fun () -> try
+  raise_failure_owned 123
+with Failure e -> let () = Util.gc () in e = "An error: 123")
+
+let%test "raise invalid argument" = Util.check_leaks (fun () -> try
+  raise_invalid_argument "abc"
+with Invalid_argument e -> let () = Util.gc () in e = "abc")
+
+exception Empty
+exception Invalid of string * int
+
+let () = Callback.register_exception "Empty" Empty
+let () = Callback.register_exception "Parse_error_invalid" (Invalid ("", 0))
+
+external raise_parse_error: string -> int = "raise_parse_error"
+
+let%test "derive exception" = Util.check_leaks (fun () ->
+  raise_parse_error "123" = 123 &&
+  (try let _ = raise_parse_error "" in false with Empty -> true) &&
+  (try let _ = raise_parse_error "abc" in false with Invalid (s, n) -> Util.gc (); s = "abc" && n = 3))
+
+external raise_parse_error_map_err: string -> int = "raise_parse_error_map_err"
+
+let%test "derive exception map_err" = Util.check_leaks (fun () ->
+  raise_parse_error_map_err "123" = 123 &&
+  (try let _ = raise_parse_error_map_err "" in false with Empty -> true))
+
 (* Hash variant *)
 type hash_variant = [
   | `Abc of int
@@ -73,3 +103,64 @@ let%test "test call named" = Util.check_leaks (fun () ->
   Util.gc ();
   x = 4.0
 )
+
+external runtime_handle_concat: string -> string -> string = "runtime_handle_concat"
+external runtime_handle_many: int -> int -> int -> int -> int -> int -> int array = "runtime_handle_many_bytecode" "runtime_handle_many"
+
+let%test "runtime handle" = Util.check_leaks (fun () ->
+  let s = runtime_handle_concat "abc" "def" in
+  Util.gc ();
+  s = "abcdef"
+)
+
+let%test "runtime handle (bytecode)" = Util.check_leaks (fun () ->
+  let a = runtime_handle_many 1 2 3 4 5 6 in
+  Util.gc ();
+  a = [| 1; 2; 3; 4; 5; 6 |]
+)
+
+exception Rust_panic of string * string * int
+
+let () = Callback.register_exception "Rust_panic" (Rust_panic ("", "", 0))
+
+external set_panic_policy_exception: bool -> unit = "set_panic_policy_exception"
+external test_panic_drop: unit -> int = "test_panic_drop"
+
+let%test "test panic policy exception" = Util.check_leaks (fun () ->
+  set_panic_policy_exception true;
+  let res = try
+    let _ = test_panic_drop () in
+    false
+  with
+    | Rust_panic (_, file, line) -> Filename.basename file = "runtime.rs" && line > 0
+    | _ -> false
+  in
+  set_panic_policy_exception false;
+  res)
+
+(* Marshal *)
+
+external marshal_to_bytes: 'a -> bool -> bytes = "marshal_to_bytes"
+external marshal_from_bytes: bytes -> 'a = "marshal_from_bytes"
+
+let%test "marshal" = Util.check_leaks (fun () ->
+  let s = "abc" in
+  let x = (1, [s; s], Some 2.5) in
+  Util.gc ();
+  marshal_to_bytes x false = Marshal.to_bytes x [] &&
+  marshal_to_bytes x true = Marshal.to_bytes x [Marshal.No_sharing] &&
+  marshal_from_bytes (Marshal.to_bytes x []) = x)
+
+external marshal_empty_double_array: unit -> bytes = "marshal_empty_double_array"
+
+let%test "marshal empty float array" = Util.check_leaks (fun () ->
+  let a : float array = [||] in
+  marshal_empty_double_array () = Marshal.to_bytes a [])
+
+let%test "marshal invalid" = Util.check_leaks (fun () ->
+  try let _ = marshal_from_bytes (Bytes.of_string "abc") in false
+  with Failure _ -> true)
+
+let%test "marshal closure" = Util.check_leaks (fun () ->
+  try let _ = marshal_to_bytes (fun x -> x + 1) false in false
+  with Invalid_argument _ -> true)
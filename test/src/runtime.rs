@@ -54,6 +54,54 @@ pub fn raise_failure() -> Result<(), ocaml::Error> {
     ocaml::Error::failwith("An error")
 }
 
+#[ocaml::func]
+pub fn raise_failure_owned(i: ocaml::Int) -> Result<(), ocaml::Error> {
+    ocaml::Error::failwith(format!("An error: {}", i))
+}
+
+#[ocaml::func]
+pub fn raise_invalid_argument(s: String) -> Result<(), ocaml::Error> {
+    ocaml::Error::invalid_argument(s)
+}
+
+#[derive(Debug, ocaml::Exception)]
+pub enum ParseError {
+    Empty,
+    #[ocaml(name = "Parse_error_invalid")]
+    Invalid(String, ocaml::Int),
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "empty input"),
+            ParseError::Invalid(s, _) => write!(f, "invalid integer: {}", s),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+fn parse_int(s: &str) -> Result<ocaml::Int, ParseError> {
+    if s.is_empty() {
+        return Err(ParseError::Empty);
+    }
+
+    s.parse()
+        .map_err(|_| ParseError::Invalid(s.to_string(), s.len() as ocaml::Int))
+}
+
+#[ocaml::func]
+pub fn raise_parse_error(s: &str) -> Result<ocaml::Int, ParseError> {
+    parse_int(s)
+}
+
+#[ocaml::func]
+pub fn raise_parse_error_map_err(s: &str) -> Result<ocaml::Int, ocaml::Error> {
+    let i = parse_int(s).map_err(ocaml::IntoException::into_exception)?;
+    Ok(i)
+}
+
 #[ocaml::func]
 pub fn hash_variant_abc(i: ocaml::Int) -> Value {
     Value::hash_variant("Abc", Some(Value::int(i)))
@@ -74,3 +122,63 @@ pub fn test_call_named(g: ocaml::Float) -> Result<ocaml::Value, ocaml::Error> {
     let f: Value = Value::named("call_named").unwrap();
     f.call(g)
 }
+
+#[ocaml::func]
+pub fn runtime_handle_concat(rt: &mut ocaml::Runtime, a: String, b: String) -> ocaml::Value {
+    let s = rt.alloc(a + &b);
+    s.value()
+}
+
+#[ocaml::func]
+pub fn runtime_handle_many(
+    rt: &mut ocaml::Runtime,
+    a: ocaml::Int,
+    b: ocaml::Int,
+    c: ocaml::Int,
+    d: ocaml::Int,
+    e: ocaml::Int,
+    f: ocaml::Int,
+) -> ocaml::Value {
+    let root = rt.alloc(vec![a, b, c, d, e, f]).root();
+    rt.unlocked(|| ());
+    root.value()
+}
+
+#[ocaml::func]
+pub fn set_panic_policy_exception(use_exception: bool) {
+    if use_exception {
+        ocaml::set_panic_policy(ocaml::PanicPolicy::Exception("Rust_panic"));
+    } else {
+        ocaml::set_panic_policy(ocaml::PanicPolicy::Raise);
+    }
+}
+
+#[ocaml::func]
+pub fn test_panic_drop() -> ocaml::Int {
+    let _s = String::from("dropped before raising");
+    let v: Vec<ocaml::Int> = Vec::new();
+    v[0]
+}
+
+#[ocaml::func]
+pub unsafe fn marshal_to_bytes(v: Value, no_sharing: bool) -> Value {
+    let flags: &[ocaml::marshal::Flag] = if no_sharing {
+        &[ocaml::marshal::Flag::NoSharing]
+    } else {
+        &[]
+    };
+    let bytes = ocaml::marshal::to_bytes(v, flags);
+    ocaml::ToValue::to_value(bytes.as_slice())
+}
+
+#[ocaml::func]
+pub fn marshal_empty_double_array() -> Result<Value, ocaml::Error> {
+    let bytes = ocaml::marshal::Data::DoubleArray(vec![]).to_bytes()?;
+    Ok(ocaml::ToValue::to_value(bytes.as_slice()))
+}
+
+#[ocaml::func]
+pub unsafe fn marshal_from_bytes(b: &[u8]) -> Result<Value, ocaml::Error> {
+    // The input can't point to the OCaml heap
+    ocaml::marshal::from_bytes(&b.to_vec())
+}
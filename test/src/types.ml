@@ -59,6 +59,72 @@ let%test "array1 from rust vec" = Util.check_leaks (fun () ->
   a.{4} = 5.
 )
 
+external array1_from_vec: int -> (int64, int64_elt, c_layout) Array1.t = "array1_from_vec"
+
+let%test "array1 from vec sub" = Util.check_leaks (fun () ->
+  let sub =
+    let a = array1_from_vec 100 in
+    Array1.sub a 10 5
+  in
+  Util.gc ();
+  Array1.dim sub = 5 && sub.{0} = 10L && sub.{4} = 14L
+)
+let%test "array1 from vec compare" = Util.check_leaks (fun () ->
+  let a = array1_from_vec 10 in
+  let b : (int64, int64_elt, c_layout) Array1.t = Marshal.from_string (Marshal.to_string a []) 0 in
+  Util.gc ();
+  a = b && Array1.dim (array1_from_vec 0) = 0
+)
+external array1_sub: (int64, int64_elt, c_layout) Array1.t -> int -> int -> (int64, int64_elt, c_layout) Array1.t = "array1_sub"
+external array1_sub_fortran: (float, float64_elt, fortran_layout) Array1.t -> int -> int -> (float, float64_elt, fortran_layout) Array1.t = "array1_sub_fortran"
+external array1_blit: (int64, int64_elt, c_layout) Array1.t -> (int64, int64_elt, c_layout) Array1.t -> unit = "array1_blit"
+external array1_reverse: (int64, int64_elt, c_layout) Array1.t -> unit = "array1_reverse"
+
+let%test "array1 sub" = Util.check_leaks (fun () ->
+  let a = array1_from_vec 10 in
+  let sub = array1_sub a 2 3 in
+  sub.{0} <- 100L;
+  let sub = array1_sub sub 1 2 in
+  Util.gc ();
+  Array1.dim sub = 2 && sub.{0} = 3L && sub.{1} = 4L && a.{2} = 100L
+)
+let%test "array1 sub outlives parent" = Util.check_leaks (fun () ->
+  let sub = array1_sub (array1_from_vec 100) 90 10 in
+  Util.gc ();
+  Array1.dim sub = 10 && sub.{0} = 90L && sub.{9} = 99L
+)
+let%test "array1 sub fortran" = Util.check_leaks (fun () ->
+  let a = Array1.of_array float64 fortran_layout [| 1.; 2.; 3.; 4. |] in
+  let sub = array1_sub_fortran a 1 2 in
+  Array1.dim sub = 2 && sub.{1} = 2. && sub.{2} = 3.
+)
+let%test "array1 bad sub" = Util.check_leaks (fun () ->
+  try ignore (array1_sub (array1_from_vec 10) 8 3); false with Invalid_argument _ -> true
+)
+let%test "array1 blit" = Util.check_leaks (fun () ->
+  let a = array1_from_vec 10 in
+  array1_blit (array1_sub a 0 5) (array1_sub a 5 5);
+  array1_reverse (array1_sub a 0 3);
+  a = Array1.of_array int64 c_layout [| 2L; 1L; 0L; 3L; 4L; 0L; 1L; 2L; 3L; 4L |]
+  && (try array1_blit a (array1_sub a 0 5); false with Invalid_argument _ -> true)
+)
+
+external array1_complex_sum: (Complex.t, complex64_elt, c_layout) Array1.t -> float * float = "array1_complex_sum"
+external array1_int_sum: (int, int_elt, c_layout) Array1.t -> int = "array1_int_sum"
+external array1_int_sum_unchecked: (float, float32_elt, c_layout) Array1.t -> int = "array1_int_sum"
+
+let%test "array1 complex" = Util.check_leaks (fun () ->
+  let a = Array1.of_array complex64 c_layout [| Complex.one; Complex.i; { Complex.re = 2.; im = 3. } |] in
+  array1_complex_sum a = (3., 4.)
+)
+let%test "array1 caml_int" = Util.check_leaks (fun () ->
+  array1_int_sum (Array1.of_array int c_layout [| 1; -2; max_int; min_int |]) = -2
+)
+let%test "array1 kind mismatch" = Util.check_leaks (fun () ->
+  try ignore (array1_int_sum_unchecked (Array1.create float32 c_layout 4)); false
+  with Invalid_argument _ -> true
+)
+
 type array2_t = (float, float32_elt, c_layout) Array2.t
 
 external make_array2: int -> int -> array2_t = "make_array2"
@@ -94,6 +160,57 @@ let%test "array2_format" = Util.check_leaks (fun () ->
   check && (array2_format arr) = "[[0, 0, 0], [0, 1, 2], [0, 2, 4]]"
 )
 
+external genarray_sum: (float, float64_elt, c_layout) Genarray.t -> int * float = "genarray_sum"
+external genarray_range: int array -> (float, float64_elt, c_layout) Genarray.t = "genarray_range"
+
+let%test "genarray" = Util.check_leaks (fun () ->
+  let a = Genarray.init float64 c_layout [| 2; 3; 4; 5 |] (fun idx -> float_of_int idx.(3)) in
+  genarray_sum a = (4, 240.)
+)
+let%test "genarray from ndarray" = Util.check_leaks (fun () ->
+  let a = genarray_range [| 2; 3 |] in
+  Util.gc ();
+  Genarray.dims a = [| 3; 2 |] && Genarray.get a [| 0; 1 |] = 3. && Genarray.get a [| 2; 1 |] = 5.
+)
+external genarray_slice_left: (float, float64_elt, c_layout) Genarray.t -> int array -> (float, float64_elt, c_layout) Genarray.t = "genarray_slice_left"
+
+let%test "genarray slice_left" = Util.check_leaks (fun () ->
+  let row =
+    let a = Genarray.init float64 c_layout [| 3; 4; 5 |] (fun idx -> float_of_int (idx.(0) * 100 + idx.(1) * 10 + idx.(2))) in
+    genarray_slice_left a [| 2; 1 |]
+  in
+  Util.gc ();
+  Genarray.dims row = [| 5 |] && Genarray.get row [| 0 |] = 210. && Genarray.get row [| 4 |] = 214.
+  && (try ignore (genarray_slice_left row [| 5 |]); false with Invalid_argument _ -> true)
+)
+let%test "genarray too many dims" = Util.check_leaks (fun () ->
+  try ignore (genarray_range (Array.make 17 1)); false with Invalid_argument _ -> true
+)
+
+external array2_fortran_row: (float, float64_elt, fortran_layout) Array2.t -> int -> float array = "array2_fortran_row"
+external array2_fortran_row_unchecked: (float, float64_elt, c_layout) Array2.t -> int -> float array = "array2_fortran_row"
+external array2_fortran_range: int -> int -> (float, float64_elt, fortran_layout) Array2.t = "array2_fortran_range"
+external genarray_fortran_range: int array -> (float, float64_elt, fortran_layout) Genarray.t = "genarray_fortran_range"
+
+let%test "array2 fortran view" = Util.check_leaks (fun () ->
+  let a = Array2.of_array float64 fortran_layout [| [| 1.; 2.; 3. |]; [| 4.; 5.; 6. |] |] in
+  array2_fortran_row a 0 = [| 1.; 2.; 3. |] && array2_fortran_row a 1 = [| 4.; 5.; 6. |]
+)
+let%test "array2 fortran create" = Util.check_leaks (fun () ->
+  let a = array2_fortran_range 2 3 in
+  Util.gc ();
+  Array2.dim1 a = 2 && Array2.dim2 a = 3 && a.{1, 1} = 0. && a.{1, 3} = 2. && a.{2, 1} = 3.
+)
+let%test "genarray fortran create" = Util.check_leaks (fun () ->
+  let a = genarray_fortran_range [| 2; 3 |] in
+  Util.gc ();
+  Genarray.get a [| 1; 1 |] = 0. && Genarray.get a [| 2; 1 |] = 1. && Genarray.get a [| 1; 3 |] = 4.
+)
+let%test "array2 layout mismatch" = Util.check_leaks (fun () ->
+  try ignore (array2_fortran_row_unchecked (Array2.create float64 c_layout 2 2) 0); false
+  with Invalid_argument _ -> true
+)
+
 type abstract_ptr
 
 external alloc_abstract_pointer : float -> abstract_ptr = "alloc_abstract_pointer"
@@ -1,4 +1,5 @@
 use ocaml::Value;
+use std::convert::TryFrom;
 
 #[ocaml::func]
 pub fn list_length(x: ocaml::List<ocaml::Value>) -> ocaml::Int {
@@ -65,6 +66,59 @@ pub fn array1_from_rust_vec() -> ocaml::bigarray::Array1<f32> {
     vec![1f32, 2f32, 3f32, 4f32, 5f32].into()
 }
 
+#[ocaml::func]
+pub fn array1_complex_sum(
+    arr: ocaml::bigarray::Array1<ocaml::bigarray::Complex<f64>>,
+) -> (f64, f64) {
+    arr.data()
+        .iter()
+        .fold((0.0, 0.0), |(re, im), x| (re + x.re, im + x.im))
+}
+
+#[ocaml::func]
+pub fn array1_int_sum(arr: ocaml::bigarray::Array1<ocaml::bigarray::CamlInt>) -> ocaml::Int {
+    arr.data().iter().map(|x| x.0).sum()
+}
+
+#[ocaml::func]
+pub fn array1_from_vec(len: usize) -> ocaml::bigarray::Array1<i64> {
+    ocaml::bigarray::Array1::from_vec((0..len as i64).collect())
+}
+
+#[ocaml::func]
+pub fn array1_sub(
+    arr: ocaml::bigarray::Array1<i64>,
+    offset: usize,
+    len: usize,
+) -> Result<ocaml::bigarray::Array1<i64>, ocaml::Error> {
+    arr.sub(offset, len)
+}
+
+#[ocaml::func]
+pub fn array1_sub_fortran(
+    arr: ocaml::bigarray::Array1<f64, ocaml::bigarray::FortranLayout>,
+    offset: usize,
+    len: usize,
+) -> Result<ocaml::bigarray::Array1<f64, ocaml::bigarray::FortranLayout>, ocaml::Error> {
+    arr.sub(offset, len)
+}
+
+#[ocaml::func]
+pub fn array1_blit(
+    src: ocaml::bigarray::Array1<i64>,
+    mut dst: ocaml::bigarray::Array1<i64>,
+) -> Result<(), ocaml::Error> {
+    src.blit(&mut dst)
+}
+
+#[ocaml::func]
+pub fn array1_reverse(mut arr: ocaml::bigarray::Array1<i64>) -> Result<(), ocaml::Error> {
+    let mut items = vec![0; arr.len()];
+    arr.copy_to_slice(&mut items)?;
+    items.reverse();
+    arr.blit_from_slice(&items)
+}
+
 #[ocaml::func]
 pub fn make_array2(dim1: usize, dim2: usize) -> ocaml::bigarray::Array2<f32> {
     let arr = ndarray::Array2::zeros((dim1, dim2));
@@ -88,6 +142,56 @@ pub fn array2_format(arr: ocaml::bigarray::Array2<f32>) -> String {
     format!("{}", arr.view()).replace("\n", "")
 }
 
+#[ocaml::func]
+pub fn genarray_sum(arr: ocaml::bigarray::Genarray<f64>) -> (usize, f64) {
+    (arr.ndim(), arr.view().sum())
+}
+
+#[ocaml::func]
+pub fn genarray_range(shape: Vec<usize>) -> Result<ocaml::bigarray::Genarray<f64>, ocaml::Error> {
+    let len = shape.iter().product::<usize>();
+    let arr = ndarray::ArrayD::from_shape_vec(shape, (0..len).map(|x| x as f64).collect())
+        .map_err(|e| ocaml::Error::Message(e.to_string().into()))?;
+    // Transposing produces an array that isn't in standard layout
+    ocaml::bigarray::Genarray::try_from(arr.reversed_axes())
+}
+
+#[ocaml::func]
+pub fn genarray_slice_left(
+    arr: ocaml::bigarray::Genarray<f64>,
+    index: Vec<usize>,
+) -> Result<ocaml::bigarray::Genarray<f64>, ocaml::Error> {
+    arr.slice_left(&index)
+}
+
+#[ocaml::func]
+pub fn array2_fortran_row(
+    arr: ocaml::bigarray::Array2<f64, ocaml::bigarray::FortranLayout>,
+    row: usize,
+) -> Vec<f64> {
+    arr.view().row(row).to_vec()
+}
+
+#[ocaml::func]
+pub fn array2_fortran_range(
+    rows: usize,
+    cols: usize,
+) -> ocaml::bigarray::Array2<f64, ocaml::bigarray::FortranLayout> {
+    ndarray::Array2::from_shape_fn((rows, cols), |(i, j)| (i * cols + j) as f64).into()
+}
+
+#[ocaml::func]
+pub fn genarray_fortran_range(
+    shape: Vec<usize>,
+) -> Result<ocaml::bigarray::Genarray<f64, ocaml::bigarray::FortranLayout>, ocaml::Error> {
+    use ndarray::ShapeBuilder;
+    let len = shape.iter().product::<usize>();
+    // Column-major data is moved into the bigarray without copying
+    let arr = ndarray::ArrayD::from_shape_vec(shape.f(), (0..len).map(|x| x as f64).collect())
+        .map_err(|e| ocaml::Error::Message(e.to_string().into()))?;
+    ocaml::bigarray::Genarray::try_from(arr)
+}
+
 #[derive(Debug)]
 struct Abstract {
     f: f64,